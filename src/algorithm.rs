@@ -0,0 +1,595 @@
+use good_lp::ResolutionError;
+
+const PIVOT_TOL: f64 = 1e-9;
+const OPTIMALITY_TOL: f64 = 1e-9;
+const FEASIBILITY_TOL: f64 = 1e-7;
+
+/// Rule used to select the entering column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotRule {
+    /// Largest reduced cost
+    Dantzig,
+    /// Smallest index with positive reduced cost, guaranteed not to cycle
+    Bland,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimplexOptions {
+    pub pivot_rule: PivotRule,
+    /// Number of consecutive degenerate pivots before switching to Bland's rule
+    pub max_degenerate_pivots: usize,
+    /// Tolerance of the Harris ratio test, 0 uses the textbook ratio test
+    pub harris_tolerance: f64,
+    /// Relative size of the right-hand side perturbation, 0 disables it
+    pub perturbation: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for SimplexOptions {
+    fn default() -> Self {
+        SimplexOptions {
+            pivot_rule: PivotRule::Dantzig,
+            max_degenerate_pivots: 20,
+            harris_tolerance: 1e-9,
+            perturbation: 1e-9,
+            max_iterations: 50_000,
+        }
+    }
+}
+
+/// Optimal values of all variable and slack columns together with the final basis
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplexResult {
+    pub values: Vec<f64>,
+    pub basis: Vec<usize>,
+    /// Dual value of every row, `c_B^T B^-1`
+    pub duals: Vec<f64>,
+    /// Reduced cost of every column, `c_j - duals^T A_j`
+    pub reduced_costs: Vec<f64>,
+    /// Change of each objective coefficient for which the basis stays optimal
+    pub cost_ranges: Vec<(f64, f64)>,
+    /// Change of each right-hand side for which the basis stays feasible
+    pub rhs_ranges: Vec<(f64, f64)>,
+}
+
+/// Row-wise simplex tableau for `max c^T x, Ax = b, x >= 0`
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    reduced_costs: Vec<f64>,
+    rhs: Vec<f64>,
+    /// Perturbation of `rhs`, transformed by the same pivots so it can be removed again
+    pert: Vec<f64>,
+    basis: Vec<usize>,
+    /// `B^-1` with respect to the original rows, used for the dual values
+    inverse: Vec<Vec<f64>>,
+    objective: Vec<f64>,
+    num_cols: usize,
+}
+
+impl Tableau {
+    /// Builds the tableau from the column layout produced by `MySolver`:
+    /// an unused first column, the variable and slack columns, and the right-hand side as last column.
+    /// The first entry of every column belongs to the objective row.
+    fn from_columns(table: &[Vec<f64>], basic_var_cols: &[usize], perturbation: f64) -> Self {
+        let columns = &table[1..table.len()-1];
+        let rhs_col = &table[table.len()-1];
+        let num_rows = rhs_col.len() - 1;
+        let num_cols = columns.len();
+
+        let mut rows: Vec<Vec<f64>> = (1..=num_rows)
+            .map(|i| columns.iter().map(|col| col[i]).collect())
+            .collect();
+        let mut rhs: Vec<f64> = rhs_col[1..].to_vec();
+
+        // Right-hand sides have to be non-negative for the initial basis
+        let mut inverse = vec![vec![0.; num_rows]; num_rows];
+        for (i, (row, b)) in rows.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            inverse[i][i] = 1.;
+            if *b < 0. {
+                row.iter_mut().for_each(|a| *a = -*a);
+                *b = -*b;
+                inverse[i][i] = -1.;
+            }
+        }
+
+        let mut basis = vec![usize::MAX; num_rows];
+        for &col in basic_var_cols {
+            let unit_row = (0..num_rows).find(|&i| rows[i][col] == 1.);
+            let Some(i) = unit_row else { continue };
+            if basis[i] == usize::MAX && (0..num_rows).all(|k| k == i || rows[k][col] == 0.) {
+                basis[i] = col;
+            }
+        }
+
+        // Distinct perturbations per row break ties in the ratio test
+        let pert = rhs.iter().enumerate()
+            .map(|(i, b)| perturbation * (1. + b.abs()) * (1. + i as f64 / num_rows as f64))
+            .collect();
+
+        let objective: Vec<f64> = columns.iter().map(|col| col[0]).collect();
+        Tableau {
+            rows,
+            reduced_costs: objective.clone(),
+            rhs,
+            pert,
+            basis,
+            inverse,
+            objective,
+            num_cols,
+        }
+    }
+
+    fn pivot(&mut self, row: usize, col: usize) {
+        let factor = self.rows[row][col];
+        self.rows[row].iter_mut().for_each(|a| *a /= factor);
+        self.rhs[row] /= factor;
+        self.pert[row] /= factor;
+        self.inverse[row].iter_mut().for_each(|a| *a /= factor);
+
+        let pivot_row = self.rows[row].clone();
+        let pivot_inverse = self.inverse[row].clone();
+        for i in 0..self.rows.len() {
+            let a = self.rows[i][col];
+            if i == row || a == 0. { continue; }
+            self.rows[i].iter_mut().zip(&pivot_row).for_each(|(x, p)| *x -= a * p);
+            self.rows[i][col] = 0.;
+            self.rhs[i] -= a * self.rhs[row];
+            self.pert[i] -= a * self.pert[row];
+            self.inverse[i].iter_mut().zip(&pivot_inverse).for_each(|(x, p)| *x -= a * p);
+        }
+
+        let d = self.reduced_costs[col];
+        self.reduced_costs.iter_mut().zip(&pivot_row).for_each(|(x, p)| *x -= d * p);
+        self.reduced_costs[col] = 0.;
+        self.basis[row] = col;
+    }
+
+    fn entering_col(&self, rule: PivotRule, num_cols: usize) -> Option<usize> {
+        let mut candidates = (0..num_cols).filter(|&j| self.reduced_costs[j] > OPTIMALITY_TOL);
+        match rule {
+            PivotRule::Bland => candidates.next(),
+            PivotRule::Dantzig => candidates
+                .reduce(|j, k| if self.reduced_costs[k] > self.reduced_costs[j] { k } else { j }),
+        }
+    }
+
+    fn leaving_row(&self, col: usize, rule: PivotRule, harris_tolerance: f64) -> Option<usize> {
+        let candidates: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| self.rows[i][col] > PIVOT_TOL)
+            .collect();
+        let value = |i: usize| (self.rhs[i] + self.pert[i]).max(0.);
+        let ratio = |i: usize| value(i) / self.rows[i][col];
+
+        if rule == PivotRule::Bland || harris_tolerance == 0. {
+            // Textbook ratio test, ties are broken by the smallest basic index (Bland)
+            return candidates.into_iter().reduce(|i, k| {
+                let (ri, rk) = (ratio(i), ratio(k));
+                if rk < ri || (rk == ri && rule == PivotRule::Bland && self.basis[k] < self.basis[i]) { k } else { i }
+            });
+        }
+
+        // Harris ratio test: relax the bounds to find the maximum step,
+        // then choose the largest pivot element among the rows within that step
+        let max_step = candidates.iter()
+            .map(|&i| (value(i) + harris_tolerance) / self.rows[i][col])
+            .fold(f64::INFINITY, f64::min);
+
+        candidates.into_iter()
+            .filter(|&i| ratio(i) <= max_step)
+            .reduce(|i, k| if self.rows[k][col] > self.rows[i][col] { k } else { i })
+    }
+
+    /// Runs primal simplex iterations on the first `num_cols` columns until optimality
+    fn optimize(&mut self, num_cols: usize, options: &SimplexOptions, iterations: &mut usize) -> Result<(), ResolutionError> {
+        let mut num_degenerate = 0;
+        loop {
+            let rule = if num_degenerate >= options.max_degenerate_pivots {
+                PivotRule::Bland
+            } else {
+                options.pivot_rule
+            };
+
+            let Some(col) = self.entering_col(rule, num_cols) else {
+                return Ok(());
+            };
+            let Some(row) = self.leaving_row(col, rule, options.harris_tolerance) else {
+                return Err(ResolutionError::Unbounded);
+            };
+
+            *iterations += 1;
+            if *iterations > options.max_iterations {
+                return Err(ResolutionError::Other("Simplex iteration limit reached"));
+            }
+
+            let step = (self.rhs[row] + self.pert[row]).max(0.) / self.rows[row][col];
+            if step <= PIVOT_TOL { num_degenerate += 1; } else { num_degenerate = 0; }
+
+            self.pivot(row, col);
+        }
+    }
+
+    /// Phase one: finds a feasible basis by minimizing the sum of artificial variables
+    fn find_feasible_basis(&mut self, options: &SimplexOptions, iterations: &mut usize) -> Result<(), ResolutionError> {
+        let artificial_rows: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| self.basis[i] == usize::MAX)
+            .collect();
+        if artificial_rows.is_empty() { return Ok(()); }
+
+        self.reduced_costs = vec![0.; self.num_cols];
+        for (k, &i) in artificial_rows.iter().enumerate() {
+            for row in self.rows.iter_mut() {
+                row.push(0.);
+            }
+            self.rows[i][self.num_cols + k] = 1.;
+            self.basis[i] = self.num_cols + k;
+            self.reduced_costs.push(0.);
+            for j in 0..self.num_cols {
+                self.reduced_costs[j] += self.rows[i][j];
+            }
+        }
+
+        self.optimize(self.reduced_costs.len(), options, iterations)?;
+
+        let infeasibility: f64 = (0..self.rows.len())
+            .filter(|&i| self.basis[i] >= self.num_cols)
+            .map(|i| self.rhs[i])
+            .sum();
+        if infeasibility > FEASIBILITY_TOL {
+            return Err(ResolutionError::Infeasible);
+        }
+
+        // Drive remaining artificial variables out of the basis, rows without
+        // any structural entry are redundant and get removed
+        let mut redundant = Vec::new();
+        for i in 0..self.rows.len() {
+            if self.basis[i] < self.num_cols { continue; }
+            match (0..self.num_cols).find(|&j| self.rows[i][j].abs() > PIVOT_TOL) {
+                Some(j) => self.pivot(i, j),
+                None => redundant.push(i),
+            }
+        }
+        for i in redundant.into_iter().rev() {
+            self.rows.remove(i);
+            self.rhs.remove(i);
+            self.pert.remove(i);
+            self.basis.remove(i);
+            self.inverse.remove(i);
+        }
+        for row in self.rows.iter_mut() {
+            row.truncate(self.num_cols);
+        }
+
+        // Restore the original objective in terms of the new basis
+        self.reduced_costs = self.objective.clone();
+        for i in 0..self.rows.len() {
+            let c = self.reduced_costs[self.basis[i]];
+            if c == 0. { continue; }
+            for j in 0..self.num_cols {
+                self.reduced_costs[j] -= c * self.rows[i][j];
+            }
+        }
+        Ok(())
+    }
+
+    /// Dual values `c_B^T B^-1` of the original rows, redundant rows get 0
+    fn duals(&self) -> Vec<f64> {
+        let mut duals = vec![0.; self.inverse.first().map_or(0, |row| row.len())];
+        for (i, &col) in self.basis.iter().enumerate() {
+            let c = self.objective[col];
+            if c == 0. { continue; }
+            duals.iter_mut().zip(&self.inverse[i]).for_each(|(y, b)| *y += c * b);
+        }
+        duals
+    }
+
+    /// Objective ranging: nonbasic columns stay nonbasic until their reduced cost becomes positive,
+    /// a basic column changes the reduced costs of all nonbasic columns by its tableau row
+    fn cost_ranges(&self) -> Vec<(f64, f64)> {
+        let mut ranges: Vec<(f64, f64)> = self.reduced_costs.iter()
+            .map(|d| (f64::NEG_INFINITY, -d))
+            .collect();
+
+        for (r, &col) in self.basis.iter().enumerate() {
+            let (mut lower, mut upper) = (f64::NEG_INFINITY, f64::INFINITY);
+            for k in 0..self.num_cols {
+                let a = self.rows[r][k];
+                if self.basis.contains(&k) || a.abs() <= PIVOT_TOL { continue; }
+                let delta = self.reduced_costs[k] / a;
+                if a > 0. { lower = lower.max(delta); } else { upper = upper.min(delta); }
+            }
+            ranges[col] = (lower, upper);
+        }
+        ranges
+    }
+
+    /// Right-hand side ranging: the basic values move along the column of `B^-1`
+    fn rhs_ranges(&self) -> Vec<(f64, f64)> {
+        let num_rows = self.inverse.first().map_or(0, |row| row.len());
+        (0..num_rows)
+            .map(|i| {
+                let (mut lower, mut upper) = (f64::NEG_INFINITY, f64::INFINITY);
+                for (r, row) in self.inverse.iter().enumerate() {
+                    let b = row[i];
+                    if b.abs() <= PIVOT_TOL { continue; }
+                    let delta = -self.rhs[r].max(0.) / b;
+                    if b > 0. { lower = lower.max(delta); } else { upper = upper.min(delta); }
+                }
+                (lower, upper)
+            })
+            .collect()
+    }
+
+    fn values(&self) -> Vec<f64> {
+        let mut values = vec![0.; self.num_cols];
+        for (i, &col) in self.basis.iter().enumerate() {
+            values[col] = self.rhs[i];
+        }
+        values
+    }
+}
+
+/// Solves the tableau with the default options,
+/// returns the values of all variable and slack columns
+pub fn solve_simplex(table: Vec<Vec<f64>>, basic_var_cols: Vec<usize>) -> Result<Vec<f64>, ResolutionError> {
+    solve_simplex_with(&table, &basic_var_cols, &SimplexOptions::default())
+        .map(|result| result.values)
+}
+
+pub fn solve_simplex_with(table: &[Vec<f64>], basic_var_cols: &[usize], options: &SimplexOptions) -> Result<SimplexResult, ResolutionError> {
+    let mut tableau = Tableau::from_columns(table, basic_var_cols, options.perturbation);
+    let mut iterations = 0;
+
+    tableau.find_feasible_basis(options, &mut iterations)?;
+    tableau.optimize(tableau.num_cols, options, &mut iterations)?;
+
+    let values = tableau.values();
+    if values.iter().any(|v| *v < -FEASIBILITY_TOL) {
+        // Basis is only feasible for the perturbed problem -> solve again without perturbation
+        if options.perturbation == 0. {
+            return Err(ResolutionError::Other("Simplex lost feasibility"));
+        }
+        let options = SimplexOptions { perturbation: 0., ..options.clone() };
+        return solve_simplex_with(table, basic_var_cols, &options);
+    }
+
+    Ok(SimplexResult {
+        values: values.into_iter().map(|v| v.max(0.)).collect(),
+        duals: tableau.duals(),
+        cost_ranges: tableau.cost_ranges(),
+        rhs_ranges: tableau.rhs_ranges(),
+        reduced_costs: tableau.reduced_costs,
+        basis: tableau.basis,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use good_lp::ResolutionError;
+    use super::{solve_simplex, solve_simplex_with, SimplexOptions, PivotRule};
+
+    /// Beale's example, cycles with the largest coefficient rule and the textbook ratio test
+    fn beale_table() -> (Vec<Vec<f64>>, Vec<usize>) {
+        let table = vec![
+            vec![0.; 4],
+            vec![0.75, 0.25, 0.5, 0.],
+            vec![-20., -8., -12., 0.],
+            vec![0.5, -1., -0.5, 1.],
+            vec![-6., 9., 3., 0.],
+            vec![0., 1., 0., 0.],
+            vec![0., 0., 1., 0.],
+            vec![0., 0., 0., 1.],
+            vec![0., 0., 0., 1.],
+        ];
+        (table, vec![4, 5, 6])
+    }
+
+    fn objective(table: &[Vec<f64>], values: &[f64]) -> f64 {
+        values.iter().zip(&table[1..]).map(|(v, col)| v * col[0]).sum()
+    }
+
+    #[test]
+    fn test_simple() {
+        let table = vec![
+            vec![0.; 3],
+            vec![7., 2., 3.],
+            vec![6., 4., 2.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 16., 12.],
+        ];
+        let values = solve_simplex(table, vec![2, 3]).unwrap();
+        assert!((values[0] - 2.).abs() < 1e-6);
+        assert!((values[1] - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_duals() {
+        let table = vec![
+            vec![0.; 3],
+            vec![7., 2., 3.],
+            vec![6., 4., 2.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 16., 12.],
+        ];
+        let result = solve_simplex_with(&table, &[2, 3], &SimplexOptions::default()).unwrap();
+        // 2 y1 + 3 y2 = 7, 4 y1 + 2 y2 = 6
+        assert!((result.duals[0] - 0.5).abs() < 1e-6);
+        assert!((result.duals[1] - 2.).abs() < 1e-6);
+        assert!(result.reduced_costs.iter().all(|d| *d < 1e-9));
+        assert!((result.reduced_costs[2] + 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ranging() {
+        let table = vec![
+            vec![0.; 3],
+            vec![7., 2., 3.],
+            vec![6., 4., 2.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 16., 12.],
+        ];
+        let result = solve_simplex_with(&table, &[2, 3], &SimplexOptions::default()).unwrap();
+
+        // Optimal while 0.5 <= c1 / c2 <= 1.5
+        let (lower, upper) = result.cost_ranges[0];
+        assert!((lower + 4.).abs() < 1e-6 && (upper - 2.).abs() < 1e-6);
+        let (lower, upper) = result.cost_ranges[1];
+        assert!((lower + 4. / 3.).abs() < 1e-6 && (upper - 8.).abs() < 1e-6);
+        // Nonbasic slack
+        assert_eq!(result.cost_ranges[2].0, f64::NEG_INFINITY);
+        assert!((result.cost_ranges[2].1 - 0.5).abs() < 1e-6);
+
+        // Both right-hand sides can move within [8, 24]
+        for (range, b) in result.rhs_ranges.iter().zip([16., 12.]) {
+            assert!((b + range.0 - 8.).abs() < 1e-6);
+            assert!((b + range.1 - 24.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_duals_negated_row() {
+        // max -x1, -x1 <= -2 -> x1 = 2, objective -2 - b for rhs b = -2
+        let table = vec![
+            vec![0.; 2],
+            vec![-1., -1.],
+            vec![0., 1.],
+            vec![0., -2.],
+        ];
+        let result = solve_simplex_with(&table, &[1], &SimplexOptions::default()).unwrap();
+        assert!((result.values[0] - 2.).abs() < 1e-6);
+        assert!((result.duals[0] - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_beale_cycles_without_safeguards() {
+        let (table, basic) = beale_table();
+        let options = SimplexOptions {
+            pivot_rule: PivotRule::Dantzig,
+            max_degenerate_pivots: usize::MAX,
+            harris_tolerance: 0.,
+            perturbation: 0.,
+            max_iterations: 1000,
+        };
+        let result = solve_simplex_with(&table, &basic, &options).map(|r| r.values);
+        assert_eq!(result, Err(ResolutionError::Other("Simplex iteration limit reached")));
+    }
+
+    #[test]
+    fn test_beale_bland() {
+        let (table, basic) = beale_table();
+        let options = SimplexOptions {
+            pivot_rule: PivotRule::Bland,
+            harris_tolerance: 0.,
+            perturbation: 0.,
+            ..SimplexOptions::default()
+        };
+        let values = solve_simplex_with(&table, &basic, &options).unwrap().values;
+        assert!((objective(&table, &values) - 1.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_beale_bland_fallback() {
+        let (table, basic) = beale_table();
+        let options = SimplexOptions {
+            max_degenerate_pivots: 3,
+            harris_tolerance: 0.,
+            perturbation: 0.,
+            ..SimplexOptions::default()
+        };
+        let values = solve_simplex_with(&table, &basic, &options).unwrap().values;
+        assert!((objective(&table, &values) - 1.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_beale_harris_perturbation() {
+        let (table, basic) = beale_table();
+        let options = SimplexOptions {
+            max_degenerate_pivots: usize::MAX,
+            ..SimplexOptions::default()
+        };
+        let values = solve_simplex_with(&table, &basic, &options).unwrap().values;
+        assert!((objective(&table, &values) - 1.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_phase_one() {
+        // max x1 + x2, x1 + x2 = 4, x1 <= 3 (without slack for the equality)
+        let table = vec![
+            vec![0.; 3],
+            vec![1., 1., 1.],
+            vec![1., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 4., 3.],
+        ];
+        let values = solve_simplex(table, vec![2]).unwrap();
+        assert!((values[0] + values[1] - 4.).abs() < 1e-6);
+        assert!(values[0] <= 3. + 1e-6);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // x1 <= 1, -x1 <= -2
+        let table = vec![
+            vec![0.; 3],
+            vec![1., 1., -1.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 1., -2.],
+        ];
+        assert_eq!(solve_simplex(table, vec![1, 2]), Err(ResolutionError::Infeasible));
+    }
+
+    #[test]
+    fn test_unbounded() {
+        // max x1, -x1 + x2 <= 1
+        let table = vec![
+            vec![0.; 2],
+            vec![1., -1.],
+            vec![0., 1.],
+            vec![0., 1.],
+            vec![0., 1.],
+        ];
+        assert_eq!(solve_simplex(table, vec![2]), Err(ResolutionError::Unbounded));
+    }
+
+    #[test]
+    fn test_degenerate_ties() {
+        // max sum x_i, x_i - x_j <= 0 for all pairs, sum x_i <= 10
+        let n = 5;
+        let mut rows = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                if i == j { continue; }
+                let mut row = vec![0.; n];
+                row[i] = 1.;
+                row[j] = -1.;
+                rows.push((row, 0.));
+            }
+        }
+        rows.push((vec![1.; n], 10.));
+
+        let m = rows.len();
+        let mut table = vec![vec![0.; m+1]];
+        for j in 0..n {
+            let mut col = vec![1.];
+            col.extend(rows.iter().map(|(r, _)| r[j]));
+            table.push(col);
+        }
+        for i in 0..m {
+            let mut col = vec![0.; m+1];
+            col[i+1] = 1.;
+            table.push(col);
+        }
+        let mut rhs = vec![0.];
+        rhs.extend(rows.iter().map(|(_, b)| *b));
+        table.push(rhs);
+
+        let values = solve_simplex(table.clone(), (n..n+m).collect()).unwrap();
+        assert!((objective(&table, &values) - 10.).abs() < 1e-6);
+        for v in &values[..n] {
+            assert!((v - 2.).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::{Duration, Instant};
+use good_lp::{solvers::highs::highs, Solution, SolverModel, ResolutionError};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::Serialize;
+use crate::custom_solver::{CustomSolver, LpBackend, BranchingRule};
+use crate::mip_heuristics::MipHeuristic;
+use crate::models::{MipModel, ModelBuilder, ModelSpec};
+use crate::solution::CustomSolution;
+use crate::equivalence::objectives_equal;
+use crate::validation::{verify_solution, integer_variables, StartPolicy, ViolationReport};
+
+/// Named solver setup of a benchmark run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolverConfig {
+    pub name: String,
+    /// `None` solves the model with HiGHS directly, the remaining settings only apply to `CustomSolver`
+    pub backend: Option<LpBackend>,
+    pub mip_heuristics: Vec<MipHeuristic>,
+    pub heuristic_frequency: usize,
+    pub branching: BranchingRule,
+    pub node_limit: Option<usize>,
+    pub time_limit: Option<Duration>,
+    /// Values of the first model variables in creation order, the solver completes the remaining ones
+    pub mip_start: Option<Vec<f64>>,
+    pub start_policy: StartPolicy,
+}
+
+impl SolverConfig {
+    pub fn highs(name: &str) -> Self {
+        SolverConfig {
+            name: name.to_string(),
+            backend: None,
+            mip_heuristics: Vec::new(),
+            heuristic_frequency: 0,
+            branching: BranchingRule::LargestValue,
+            node_limit: None,
+            time_limit: None,
+            mip_start: None,
+            start_policy: StartPolicy::Repair,
+        }
+    }
+
+    pub fn custom(name: &str, backend: LpBackend) -> Self {
+        SolverConfig { backend: Some(backend), ..SolverConfig::highs(name) }
+    }
+
+    pub fn with_mip_heuristics(mut self, heuristics: &[MipHeuristic], frequency: usize) -> Self {
+        self.mip_heuristics = heuristics.to_vec();
+        self.heuristic_frequency = frequency;
+        self
+    }
+
+    pub fn with_branching(mut self, rule: BranchingRule) -> Self {
+        self.branching = rule;
+        self
+    }
+
+    pub fn with_node_limit(mut self, max_nodes: usize) -> Self {
+        self.node_limit = Some(max_nodes);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn with_mip_start(mut self, values: &[f64], policy: StartPolicy) -> Self {
+        self.mip_start = Some(values.to_vec());
+        self.start_policy = policy;
+        self
+    }
+
+    fn solve(&self, model: &MipModel) -> Result<(CustomSolution, Option<usize>), ResolutionError> {
+        let values = |solution: &dyn Solution| CustomSolution::new(
+            model.variables.iter().map(|v| solution.value(*v)).collect()
+        );
+
+        let Some(backend) = self.backend else {
+            let mut solver = model.problem.clone().using(highs);
+            for c in &model.constraints {
+                solver = solver.with(c.clone());
+            }
+            return solver.solve().map(|solution| (values(&solution), None));
+        };
+
+        let mut solver = model.problem.clone().using(CustomSolver::new)
+            .with_backend(backend)
+            .with_branching(self.branching)
+            .with_mip_heuristics(&self.mip_heuristics, self.heuristic_frequency)
+            .with_start_policy(self.start_policy);
+        if let Some(start) = &self.mip_start {
+            solver = solver.with_mip_start(model.variables.iter().copied().zip(start.iter().copied()).collect());
+        }
+        if let Some(max_nodes) = self.node_limit {
+            solver = solver.with_node_limit(max_nodes);
+        }
+        if let Some(time_limit) = self.time_limit {
+            solver = solver.with_time_limit(time_limit);
+        }
+        for c in &model.constraints {
+            solver = solver.with(c.clone());
+        }
+        solver.solve().map(|solution| (values(&solution), Some(solution.num_evals)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum RunStatus {
+    Solved,
+    Failed(String),
+}
+
+/// Result of one solver config on one model
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub config: String,
+    pub model: ModelSpec,
+    pub seed: u64,
+    pub status: RunStatus,
+    /// Values of the model variables in creation order, empty if the run failed
+    pub values: Vec<f64>,
+    pub objective: Option<f64>,
+    /// Measured independently of the solver, `None` if the run failed
+    pub violations: Option<ViolationReport>,
+    pub duration_sec: f64,
+    pub num_nodes: Option<usize>,
+}
+
+/// Disagreement of a run with the run of the first config
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Mismatch {
+    Objective { reference: String, config: String, expected: f64, found: f64 },
+    /// Only one of both runs failed
+    Status { reference: String, config: String, reference_status: RunStatus, status: RunStatus },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub runs: Vec<RunRecord>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Solves the model built with an RNG seeded by `seed` with every config
+/// and compares all runs to the run of the first config
+pub fn benchmark(builder: &impl ModelBuilder, seed: u64, configs: &[SolverConfig]) -> BenchmarkResult {
+    let model = builder.build(&mut StdRng::seed_from_u64(seed));
+    let int_vars = integer_variables(&model.problem);
+
+    let runs: Vec<_> = configs.iter()
+        .map(|config| {
+            let time_start = Instant::now();
+            let result = config.solve(&model);
+            let duration_sec = time_start.elapsed().as_secs_f64();
+
+            let (status, values, violations, num_nodes) = match result {
+                Ok((solution, num_nodes)) => {
+                    let violations = verify_solution(&model.problem, &model.constraints, &int_vars, &solution);
+                    (RunStatus::Solved, solution.values().to_vec(), Some(violations), num_nodes)
+                },
+                Err(err) => (RunStatus::Failed(err.to_string()), Vec::new(), None, None),
+            };
+            RunRecord {
+                config: config.name.clone(),
+                model: builder.spec(),
+                seed,
+                status,
+                values,
+                objective: violations.as_ref().map(|v| v.objective),
+                violations,
+                duration_sec,
+                num_nodes,
+            }
+        })
+        .collect();
+
+    let mismatches = match runs.split_first() {
+        Some((reference, others)) => others.iter().filter_map(|run| mismatch(reference, run)).collect(),
+        None => Vec::new(),
+    };
+
+    BenchmarkResult { runs, mismatches }
+}
+
+fn mismatch(reference: &RunRecord, run: &RunRecord) -> Option<Mismatch> {
+    match (reference.objective, run.objective) {
+        (Some(expected), Some(found)) => {
+            (!objectives_equal(expected, found)).then(|| Mismatch::Objective {
+                reference: reference.config.clone(),
+                config: run.config.clone(),
+                expected,
+                found,
+            })
+        },
+        (None, None) => None,
+        _ => Some(Mismatch::Status {
+            reference: reference.config.clone(),
+            config: run.config.clone(),
+            reference_status: reference.status.clone(),
+            status: run.status.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::custom_solver::{LpBackend, BranchingRule};
+    use crate::mip_heuristics::MipHeuristic;
+    use crate::models::KnapsackSpec;
+    use crate::validation::StartPolicy;
+    use super::{benchmark, Mismatch, RunStatus, SolverConfig};
+
+    #[test]
+    fn test_benchmark() {
+        let spec = KnapsackSpec{ num_items: 10, max_value: 20, capacity_ratio: 0.4 };
+        let configs = [
+            SolverConfig::custom("custom", LpBackend::Highs),
+            SolverConfig::custom("native", LpBackend::Native).with_branching(BranchingRule::MostFractional),
+            SolverConfig::custom("diving", LpBackend::Highs)
+                .with_mip_heuristics(&[MipHeuristic::FractionalDiving], 5)
+                .with_branching(BranchingRule::FirstIndex),
+            // The root relaxation is fractional, so no solution is found
+            SolverConfig::custom("one node", LpBackend::Highs).with_node_limit(1),
+            SolverConfig::custom("empty start", LpBackend::Highs).with_mip_start(&[0.; 10], StartPolicy::Repair),
+            // Packing every item exceeds the capacity
+            SolverConfig::custom("full start", LpBackend::Highs).with_mip_start(&[1.; 10], StartPolicy::Error),
+        ];
+
+        let result = benchmark(&spec, 2, &configs);
+        assert_eq!(result.runs.len(), 6);
+        let solved = [&result.runs[..3], &result.runs[4..5]].concat();
+        assert!(solved.iter().all(|run| run.status == RunStatus::Solved && run.values.len() == 10));
+        assert!(solved.iter().all(|run| run.violations.as_ref().is_some_and(|v| v.is_feasible())));
+        assert!(matches!(result.runs[3].status, RunStatus::Failed(_)));
+        assert!(matches!(&result.runs[5].status, RunStatus::Failed(msg) if msg.contains("MIP start")));
+
+        assert_eq!(result.mismatches.len(), 2);
+        assert!(matches!(&result.mismatches[0], Mismatch::Status{ config, .. } if config == "one node"));
+        assert!(matches!(&result.mismatches[1], Mismatch::Status{ config, .. } if config == "full start"));
+        assert!(serde_json::to_string(&result).is_ok());
+    }
+}
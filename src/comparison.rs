@@ -1,13 +1,20 @@
 use good_lp::{constraint, variable, variable::UnsolvedProblem,
-    ProblemVariables, solvers::highs::highs, SolverModel, Solution, Constraint, Expression, Variable};
-use crate::{custom_solver::CustomSolver, heuristics::StartHeuristic};
+    ProblemVariables, solvers::highs::highs, SolverModel, Solution, Constraint, Expression, Variable, IntoAffineExpression, ResolutionError};
+use crate::{custom_solver::{CustomSolver, LpBackend}, heuristics::StartHeuristic, solution::CustomSolution,
+    partitioning::{PartitionSolver, PartitionAlgorithm}, generators::{WeightGenerator, GeneratorSpec},
+    multiway::{MultiwayModel, MultiwayObjective, MultiwayHeuristic}, models::ModelBuilder, benchmark::{benchmark, BenchmarkResult, RunStatus, SolverConfig},
+    validation::{check_solution, verify_solution, integer_variables, StartViolation, ViolationReport}, equivalence::{compare, same_partition, Discrepancy, Verification}};
+use std::collections::HashMap;
 use std::time::Instant;
+use rand::{SeedableRng, rngs::StdRng};
 use serde::Serialize;
 
 #[derive(serde::Serialize, Debug)]
-enum UsedSolver {
+pub enum UsedSolver {
     Highs,
     Custom,
+    CompleteKarmarkarKarp,
+    CompleteGreedy,
 }
 
 #[derive(Clone)]
@@ -15,67 +22,166 @@ struct ProblemSummary {
     inner: UnsolvedProblem,
     constraints: Vec<Constraint>,
     weights: Vec<f64>,
+    /// All variables, two-way models end with `diff`, k-way models with `largest` and `smallest`
     variables: Vec<Variable>,
+    generator: GeneratorSpec,
+    /// Seed of the RNG passed to the generator
+    seed: u64,
+    num_subsets: usize,
+    /// Whether the sides must hold the same number of items, up to one for odd counts
+    balanced: bool,
+}
+
+impl ProblemSummary {
+    /// Subset of each item in the solution
+    fn subsets(&self, values: &[f64]) -> Vec<usize> {
+        let num_items = self.weights.len();
+        if self.variables.len() == num_items + 1 {
+            return values[..num_items].iter().map(|x| x.round() as usize).collect();
+        }
+        values[..num_items * self.num_subsets].chunks(self.num_subsets)
+            .map(|row| (0..row.len()).max_by(|&a, &b| row[a].total_cmp(&row[b])).unwrap_or_default())
+            .collect()
+    }
+
+    fn check(&self, values: &[f64]) -> Result<(), StartViolation> {
+        let mut solution = vec![0.; self.inner.variables.len()];
+        for (var, value) in self.variables.iter().zip(values) {
+            solution[var.index()] = *value;
+        }
+        check_solution(&self.inner, &self.constraints, &integer_variables(&self.inner), &CustomSolution::new(solution))
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct SolutionSummary {
-    used_solver: UsedSolver,
+    pub used_solver: UsedSolver,
     weights: Vec<f64>,
+    generator: GeneratorSpec,
+    seed: u64,
+    num_subsets: usize,
+    balanced: bool,
+    pub status: RunStatus,
+    /// Empty if the solver failed
     values: Vec<f64>,
+    objective: Option<f64>,
+    /// Measured on the solution as returned by the solver, `None` if it failed
+    pub violations: Option<ViolationReport>,
     pub duration_sec: f64,
     num_evals: Option<usize>,
     start_heuristic: Option<StartHeuristic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    multiway_heuristic: Option<MultiwayHeuristic>,
+    /// Set once all solvers of the comparison have run
+    pub verification: Option<Verification>,
 }
 
 impl SolutionSummary {
     fn new(
-        used_solver: UsedSolver, prob_sum: ProblemSummary, solution: impl Solution, duration_sec: f64, 
-        num_evals: Option<usize>, start_heuristic: Option<StartHeuristic>
+        used_solver: UsedSolver, prob_sum: ProblemSummary, result: Result<impl Solution, ResolutionError>,
+        duration_sec: f64, num_evals: Option<usize>, start_heuristic: Option<StartHeuristic>
     ) -> Self {
-        let vals = prob_sum.variables.iter()
-            .map(|v| solution.value(*v))
-            .collect();
+        let (status, vals, objective, violations) = match result {
+            Ok(solution) => {
+                let vals = prob_sum.variables.iter()
+                    .map(|v| solution.value(*v))
+                    .collect();
+                let objective = prob_sum.inner.objective.clone().eval_with(&solution);
+                let violations = verify_solution(
+                    &prob_sum.inner, &prob_sum.constraints, &integer_variables(&prob_sum.inner), &solution
+                );
+                (RunStatus::Solved, vals, Some(objective), Some(violations))
+            },
+            Err(err) => (RunStatus::Failed(err.to_string()), Vec::new(), None, None),
+        };
 
         SolutionSummary{
             used_solver,
-            weights: prob_sum.weights, 
+            weights: prob_sum.weights,
+            generator: prob_sum.generator,
+            seed: prob_sum.seed,
+            num_subsets: prob_sum.num_subsets,
+            balanced: prob_sum.balanced,
+            status,
             values: vals,
+            objective,
+            violations,
             duration_sec,
             num_evals,
             start_heuristic,
+            multiway_heuristic: None,
+            verification: None,
         }
     }
 }
 
-impl PartialEq for SolutionSummary {
-    fn eq(&self, other: &Self) -> bool {
-        if self.values.len() != other.values.len() {
-            return false;
-        }
+/// Checks the feasibility of every solution and compares them to the first feasible one.
+/// Partitions count as symmetric if they only differ in the numbering of the subsets, failed runs are not verified.
+fn verify(problem: &ProblemSummary, summaries: &mut [SolutionSummary]) {
+    let runs: Vec<_> = summaries.iter()
+        .map(|s| s.objective.map(|objective| (objective, problem.check(&s.values))))
+        .collect();
+    let reference = runs.iter().enumerate()
+        .find_map(|(i, run)| match run {
+            Some((objective, Ok(()))) => Some((i, *objective)),
+            _ => None,
+        });
+    let is_symmetric = |a: &[f64], b: &[f64]| same_partition(&problem.subsets(a), &problem.subsets(b));
 
-        let count = self.values.iter().zip(&other.values)
-            .map(|(v1, v2)| (v1 - 1. < 10e-6) ^ (v2 - 1. < 10e-6))
-            .count();
+    for (i, run) in runs.into_iter().enumerate() {
+        let Some((found, feasible)) = run else {
+            continue;
+        };
+        let mut discrepancies: Vec<_> = feasible.err().map(Discrepancy::Infeasible).into_iter().collect();
+        let verification = match reference {
+            Some((r, _)) if r == i => Verification::Reference,
+            Some((r, expected)) => {
+                match compare(&summaries[r].values, expected, &summaries[i].values, found, is_symmetric) {
+                    Ok(equivalence) if discrepancies.is_empty() => Verification::Equivalent(equivalence),
+                    Ok(_) => Verification::Discrepant(discrepancies),
+                    Err(discrepancy) => {
+                        discrepancies.push(discrepancy);
+                        Verification::Discrepant(discrepancies)
+                    },
+                }
+            },
+            None => Verification::Discrepant(discrepancies),
+        };
 
-        count == 0 || count == self.values.len()
+        summaries[i].verification = Some(verification);
     }
 }
 
-pub fn compare_solvers(weight_fn: impl Fn() -> Vec<f64>, start_heuristic: Option<StartHeuristic>) -> [SolutionSummary; 2] {
+/// Solves the problem with HiGHS, the custom solver and each of the given partitioning algorithms.
+/// The weights are drawn with an RNG seeded by `seed`, the summaries record it with the generator spec.
+pub fn compare_solvers(
+    generator: &impl WeightGenerator, seed: u64, start_heuristic: Option<StartHeuristic>,
+    algorithms: &[PartitionAlgorithm]
+) -> Vec<SolutionSummary> {
+
+    let problem = create_problem(generator, seed);
 
-    let problem = create_problem(weight_fn);
+    let mut solutions = vec![
+        run_with_highs_solver(problem.clone()),
+        run_with_custom_solver(problem.clone(), start_heuristic),
+    ];
+    for algorithm in algorithms {
+        solutions.push(run_with_partition_solver(problem.clone(), *algorithm));
+    }
 
-    let solution1 = run_with_highs_solver(problem.clone());
-    let solution2 = run_with_custom_solver(problem, start_heuristic);
+    verify(&problem, &mut solutions);
 
-    assert!(solution1 == solution2);
+    solutions
+}
 
-    [solution1, solution2]
+/// Solves a model of the library with HiGHS and the custom solver, the model is built with an RNG seeded by `seed`.
+/// Failed solves are recorded in the run status.
+pub fn compare_model_solvers(builder: &impl ModelBuilder, seed: u64) -> BenchmarkResult {
+    benchmark(builder, seed, &[SolverConfig::highs("highs"), SolverConfig::custom("custom", LpBackend::Highs)])
 }
 
-fn create_problem(weight_fn: impl Fn() -> Vec<f64>) -> ProblemSummary {
-    let weights_obj = weight_fn();
+fn create_problem(generator: &impl WeightGenerator, seed: u64) -> ProblemSummary {
+    let weights_obj = generator.generate(&mut StdRng::seed_from_u64(seed));
 
     let mut problem = ProblemVariables::new();
 
@@ -97,12 +203,84 @@ fn create_problem(weight_fn: impl Fn() -> Vec<f64>) -> ProblemSummary {
 
     ProblemSummary {
         inner: problem,
-        variables: vars,
+        variables: vars.into_iter().chain([diff]).collect(),
         constraints: constr,
         weights: weights_obj,
+        generator: generator.spec(),
+        seed,
+        num_subsets: 2,
+        balanced: false,
     }
 }
 
+/// Solves the balanced partitioning model with HiGHS and the custom solver,
+/// the start heuristic should be cardinality-feasible like `EqualCount` or `BalancedGreedy`
+pub fn compare_balanced_solvers(
+    generator: &impl WeightGenerator, seed: u64, start_heuristic: Option<StartHeuristic>
+) -> Vec<SolutionSummary> {
+    let problem = create_balanced_problem(generator, seed);
+
+    let mut solutions = vec![
+        run_with_highs_solver(problem.clone()),
+        run_with_custom_solver(problem.clone(), start_heuristic),
+    ];
+    verify(&problem, &mut solutions);
+
+    solutions
+}
+
+/// Two-way model where the number of items on each side differs by at most one
+fn create_balanced_problem(generator: &impl WeightGenerator, seed: u64) -> ProblemSummary {
+    let mut problem = create_problem(generator, seed);
+
+    let num_items = problem.weights.len();
+    let count: Expression = problem.variables[..num_items].iter().sum();
+    problem.constraints.push(constraint!(count.clone() >= (num_items / 2) as f64));
+    problem.constraints.push(constraint!(count <= num_items.div_ceil(2) as f64));
+    problem.balanced = true;
+
+    problem
+}
+
+/// Solves the k-way partitioning model with HiGHS and the custom solver,
+/// the custom solver starts from the given heuristic
+pub fn compare_multiway_solvers(
+    generator: &impl WeightGenerator, seed: u64, num_subsets: usize, objective: MultiwayObjective,
+    heuristic: Option<MultiwayHeuristic>
+) -> Vec<SolutionSummary> {
+    let (problem, model) = create_multiway_problem(generator, seed, num_subsets, objective);
+
+    let mut custom = run_with_custom_solver_from(problem.clone(), heuristic.map(|heu| {
+        let start = model.solution_from_heuristic(&problem.weights, heu);
+        problem.variables.iter().copied().zip(start.values().iter().copied()).collect()
+    }));
+    custom.multiway_heuristic = heuristic;
+
+    let mut solutions = vec![run_with_highs_solver(problem.clone()), custom];
+    verify(&problem, &mut solutions);
+
+    solutions
+}
+
+fn create_multiway_problem(
+    generator: &impl WeightGenerator, seed: u64, num_subsets: usize, objective: MultiwayObjective
+) -> (ProblemSummary, MultiwayModel) {
+    let weights = generator.generate(&mut StdRng::seed_from_u64(seed));
+    let model = MultiwayModel::new(&weights, num_subsets, objective);
+
+    let problem = ProblemSummary {
+        inner: model.problem.clone(),
+        constraints: model.constraints.clone(),
+        variables: model.variables(),
+        weights,
+        generator: generator.spec(),
+        seed,
+        num_subsets,
+        balanced: false,
+    };
+    (problem, model)
+}
+
 fn run_with_highs_solver(problem: ProblemSummary) -> SolutionSummary {
     let mut solver = problem.inner.clone().using(highs);
     for c in &problem.constraints {
@@ -110,10 +288,10 @@ fn run_with_highs_solver(problem: ProblemSummary) -> SolutionSummary {
     }
 
     let time_start = Instant::now();
-    let solution = solver.solve().unwrap();
+    let result = solver.solve();
     let duration = time_start.elapsed();
 
-    SolutionSummary::new(UsedSolver::Highs, problem, solution, duration.as_secs_f64(), None, None)
+    SolutionSummary::new(UsedSolver::Highs, problem, result, duration.as_secs_f64(), None, None)
 }
 
 fn run_with_custom_solver(problem: ProblemSummary, start_heuristic: Option<StartHeuristic>) -> SolutionSummary {
@@ -127,10 +305,124 @@ fn run_with_custom_solver(problem: ProblemSummary, start_heuristic: Option<Start
     }
 
     let time_start = Instant::now();
-    let solution = solver.solve().unwrap();
+    let result = solver.solve();
     let duration = time_start.elapsed();
-    let num_evals = Some(solution.num_evals);
+    let num_evals = result.as_ref().ok().map(|solution| solution.num_evals);
 
-    SolutionSummary::new(UsedSolver::Custom, problem, solution, 
+    SolutionSummary::new(UsedSolver::Custom, problem, result,
         duration.as_secs_f64(), num_evals, start_heuristic)
 }
+
+fn run_with_custom_solver_from(problem: ProblemSummary, start: Option<HashMap<Variable, f64>>) -> SolutionSummary {
+    let mut solver = problem.inner.clone().using(CustomSolver::new);
+    for c in &problem.constraints {
+        solver = solver.with(c.clone());
+    }
+
+    if let Some(start) = start {
+        solver = solver.with_mip_start(start);
+    }
+
+    let time_start = Instant::now();
+    let result = solver.solve();
+    let duration = time_start.elapsed();
+    let num_evals = result.as_ref().ok().map(|solution| solution.num_evals);
+
+    SolutionSummary::new(UsedSolver::Custom, problem, result,
+        duration.as_secs_f64(), num_evals, None)
+}
+
+fn run_with_partition_solver(problem: ProblemSummary, algorithm: PartitionAlgorithm) -> SolutionSummary {
+    let solver = PartitionSolver::new(algorithm);
+
+    let time_start = Instant::now();
+    let result = solver.solve(&problem.weights);
+    let duration = time_start.elapsed();
+
+    let used_solver = match algorithm {
+        PartitionAlgorithm::CompleteKarmarkarKarp => UsedSolver::CompleteKarmarkarKarp,
+        PartitionAlgorithm::CompleteGreedy => UsedSolver::CompleteGreedy,
+    };
+    let num_evals = Some(result.num_nodes);
+
+    SolutionSummary::new(used_solver, problem, Ok::<_, ResolutionError>(CustomSolution::new(result.values)),
+        duration.as_secs_f64(), num_evals, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use good_lp::constraint;
+    use crate::{heuristics::StartHeuristic, instances::Instance, partitioning::PartitionAlgorithm,
+        equivalence::{Equivalence, Verification}, models::KnapsackSpec, benchmark::RunStatus};
+    use super::{create_problem, create_balanced_problem, run_with_custom_solver, run_with_partition_solver, verify,
+        compare_model_solvers};
+
+    #[test]
+    fn test_verify() {
+        // Best is {10, 9} vs {8, 7, 6} with a difference of 2
+        let instance = Instance::new("small", vec![10., 9., 8., 7., 6.]);
+        let problem = create_problem(&instance, 0);
+        let mut solutions = vec![
+            run_with_custom_solver(problem.clone(), None),
+            run_with_partition_solver(problem.clone(), PartitionAlgorithm::CompleteKarmarkarKarp),
+            run_with_custom_solver(problem.clone(), None),
+            run_with_custom_solver(problem.clone(), None),
+        ];
+        solutions[2].values[..5].iter_mut().for_each(|x| *x = 1. - *x);
+        solutions[3].values[5] = 0.;
+        solutions[3].objective = Some(0.);
+
+        verify(&problem, &mut solutions);
+        assert_eq!(solutions[0].verification, Some(Verification::Reference));
+        assert!(matches!(solutions[1].verification, Some(Verification::Equivalent(_))));
+        assert_eq!(solutions[2].verification, Some(Verification::Equivalent(Equivalence::Symmetric)));
+        assert!(matches!(&solutions[3].verification, Some(Verification::Discrepant(d)) if d.len() == 2));
+    }
+
+    #[test]
+    fn test_failed_run() {
+        let instance = Instance::new("small", vec![10., 9., 8., 7., 6.]);
+        let mut infeasible = create_problem(&instance, 0);
+        let x = infeasible.variables[0];
+        infeasible.constraints.push(constraint!(x >= 2));
+
+        let mut solutions = vec![
+            run_with_custom_solver(infeasible, None),
+            run_with_partition_solver(create_problem(&instance, 0), PartitionAlgorithm::CompleteGreedy),
+        ];
+        verify(&create_problem(&instance, 0), &mut solutions);
+        assert!(matches!(solutions[0].status, RunStatus::Failed(_)));
+        assert!(solutions[0].values.is_empty() && solutions[0].violations.is_none());
+        assert_eq!(solutions[0].verification, None);
+        assert_eq!(solutions[1].verification, Some(Verification::Reference));
+    }
+
+    #[test]
+    fn test_balanced() {
+        // Without the cardinality constraint {10} vs {1, 1, 1, 1, 1, 1} has a difference of 4
+        let instance = Instance::new("giant", vec![10., 1., 1., 1., 1., 1., 1.]);
+        let problem = create_balanced_problem(&instance, 0);
+        assert_eq!(problem.constraints.len(), 4);
+
+        for heuristic in [None, Some(StartHeuristic::EqualCount), Some(StartHeuristic::BalancedGreedy)] {
+            let summary = run_with_custom_solver(problem.clone(), heuristic);
+            let count: f64 = summary.values[..7].iter().sum();
+            assert!(count.round() == 3. || count.round() == 4.);
+            let diff: f64 = summary.values.iter().zip(&summary.weights).map(|(x, w)| (2. * x - 1.) * w).sum();
+            assert!((diff.abs() - 8.).abs() < 1e-6);
+            assert!(summary.objective.is_some_and(|objective| (objective - 8.).abs() < 1e-6));
+            assert!(summary.violations.as_ref().is_some_and(|v| v.is_feasible()));
+            assert!(summary.balanced);
+        }
+    }
+
+    #[test]
+    fn test_compare_model_solvers() {
+        let spec = KnapsackSpec{ num_items: 8, max_value: 20, capacity_ratio: 0.5 };
+        let result = compare_model_solvers(&spec, 1);
+        let configs: Vec<_> = result.runs.iter().map(|run| run.config.as_str()).collect();
+        assert_eq!(configs, ["highs", "custom"]);
+        assert_eq!(result.runs[1].status, RunStatus::Solved);
+        assert!(result.runs[1].violations.as_ref().is_some_and(|v| v.is_feasible()));
+    }
+}
@@ -1,11 +1,64 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use good_lp::{constraint, solvers::highs::highs, 
     variable::UnsolvedProblem, SolverModel, 
-    Solution, Constraint, Variable, ResolutionError,
-    solvers::ObjectiveDirection,
+    Solution, Constraint, Variable, ResolutionError, IntoAffineExpression,
+    solvers::{ObjectiveDirection, Solver},
 };
+use serde::Serialize;
 use crate::heuristics::{solution_from_heuristic, StartHeuristic};
-use crate::solution::CustomSolution;
+use crate::local_search::{improve_solution, LocalSearch};
+use crate::mip_heuristics::{MipContext, MipHeuristic};
+use crate::primal_heuristic::{PrimalHeuristic, HeuristicContext, HeuristicTiming};
+use crate::validation::{check_solution, StartPolicy, StartViolation, StartOutcome};
+use crate::lns::{restrict, Neighbourhood};
+use crate::solution::{CustomSolution, IntoDuals, LpDuals};
+use crate::solver::my_solver;
+
+/// Values closer than this to an integer count as integral,
+/// LP backends report e.g. `0.9999999999999999` for binaries
+const INT_TOL: f64 = 1e-6;
+
+/// LP solver used for the node relaxations
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum LpBackend {
+    Highs,
+    Native,
+}
+
+/// Which fractional integer variable `CustomSolver` branches on
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum BranchingRule {
+    /// Largest value in the relaxation
+    LargestValue,
+    /// Fractional part closest to 0.5
+    MostFractional,
+    /// Smallest variable index
+    FirstIndex,
+}
+
+/// Solves a relaxation, returns the solution and the dual values of the constraints.
+/// Shared with the solvers of sub-MIPs.
+pub(crate) type LpSolveFn = Rc<dyn Fn(UnsolvedProblem, &[Constraint]) -> Result<(CustomSolution, Option<Vec<f64>>), ResolutionError>>;
+
+/// Wraps any good_lp solver function to solve a relaxation
+pub(crate) fn lp_solve_fn<S>(solver: S) -> LpSolveFn
+where
+    S: Solver + Clone + 'static,
+    S::Model: SolverModel<Error = ResolutionError>,
+    <S::Model as SolverModel>::Solution: IntoDuals,
+{
+    Rc::new(move |problem, constraints| {
+        let num_vars = problem.variables.len();
+        let mut model = problem.using(solver.clone());
+        for c in constraints {
+            model = model.with(c.clone());
+        }
+        let solution = model.solve()?;
+        Ok((CustomSolution::from(&solution, num_vars, 0), solution.into_duals()))
+    })
+}
 
 pub struct CustomSolver {
     problem: UnsolvedProblem,
@@ -14,6 +67,25 @@ pub struct CustomSolver {
     best_solution: Option<CustomSolution>,
     fn_is_better: fn(f64, f64) -> bool,
     num_evals: usize,
+    /// Heuristic solutions that did not fit the model
+    num_rejected: usize,
+    solve_lp: LpSolveFn,
+    /// Weights and method to improve incumbents of partitioning problems
+    local_search: Option<(Vec<f64>, LocalSearch)>,
+    primal_heuristics: Vec<(Box<dyn PrimalHeuristic>, HeuristicTiming)>,
+    start_policy: StartPolicy,
+    /// Passed on to the returned solution
+    start_outcome: Option<StartOutcome>,
+    mip_start: Option<HashMap<Variable, f64>>,
+    mip_start_outcome: Option<StartOutcome>,
+    max_nodes: Option<usize>,
+    time_limit: Option<Duration>,
+    deadline: Option<Instant>,
+    branching: BranchingRule,
+    neighbourhoods: Vec<Neighbourhood>,
+    lns_frequency: usize,
+    /// Node limit of each sub-MIP
+    lns_nodes: usize,
 }
 
 impl CustomSolver {
@@ -40,35 +112,331 @@ impl CustomSolver {
             best_solution: None,
             fn_is_better,
             num_evals: 0,
+            num_rejected: 0,
+            solve_lp: lp_solve_fn(highs),
+            local_search: None,
+            primal_heuristics: Vec::new(),
+            start_policy: StartPolicy::Repair,
+            start_outcome: None,
+            mip_start: None,
+            mip_start_outcome: None,
+            max_nodes: None,
+            time_limit: None,
+            deadline: None,
+            branching: BranchingRule::LargestValue,
+            neighbourhoods: Vec::new(),
+            lns_frequency: 0,
+            lns_nodes: 0,
+        }
+    }
+
+    pub fn with_backend(self, backend: LpBackend) -> Self {
+        match backend {
+            LpBackend::Highs => self.with_lp_solver(highs),
+            LpBackend::Native => self.with_lp_solver(my_solver),
         }
     }
 
+    /// Uses any good_lp solver function (e.g. `highs`) for the node relaxations
+    pub fn with_lp_solver<S>(mut self, solver: S) -> Self
+    where
+        S: Solver + Clone + 'static,
+        S::Model: SolverModel<Error = ResolutionError>,
+        <S::Model as SolverModel>::Solution: IntoDuals,
+    {
+        self.solve_lp = lp_solve_fn(solver);
+        self
+    }
+
     pub fn add_heuristic(mut self, obj_vals: &Vec<f64>, start_heuristic: StartHeuristic) -> Self {
         self.best_solution = solution_from_heuristic(obj_vals, start_heuristic);
         self
     }
 
-    fn solve_rec(&mut self, mut constraints: Vec<Constraint>) {
-        self.num_evals += 1;
+    pub fn with_start_policy(mut self, policy: StartPolicy) -> Self {
+        self.start_policy = policy;
+        self
+    }
+
+    /// Uses a partial or complete assignment as start solution, the remaining variables
+    /// are completed by solving the relaxation with the given ones fixed
+    pub fn with_mip_start(mut self, start: HashMap<Variable, f64>) -> Self {
+        self.mip_start = Some(start);
+        self
+    }
+
+    /// Fixes the given variables and dives to an integral solution from the relaxation
+    fn complete_mip_start(&self, start: &HashMap<Variable, f64>) -> Option<CustomSolution> {
+        let mut problem = self.problem.clone();
+        for (var, value) in start {
+            let def = problem.variables.variables.get_mut(var.index())?;
+            let value = match self.start_policy {
+                StartPolicy::Repair if self.int_vars.contains(var) => value.round().clamp(def.min, def.max),
+                _ => *value,
+            };
+            def.min = value;
+            def.max = value;
+        }
+
+        let (relaxation, _) = (self.solve_lp)(problem.clone(), &self.constraints).ok()?;
+        MipContext {
+            problem: &problem,
+            constraints: &self.constraints,
+            int_vars: &self.int_vars,
+            solve_lp: &self.solve_lp,
+            incumbent: None,
+        }.run(MipHeuristic::FractionalDiving, &relaxation)
+    }
+
+    /// Completes the MIP start and keeps it if it is better than the start heuristic
+    fn apply_mip_start(&mut self) -> Result<(), ResolutionError> {
+        let Some(start) = self.mip_start.take() else {
+            return Ok(());
+        };
+
+        let completed = self.complete_mip_start(&start)
+            .ok_or(StartViolation::NoCompletion)
+            .and_then(|solution| check_solution(&self.problem, &self.constraints, &self.int_vars, &solution)
+                .map(|_| solution));
+
+        match completed {
+            Ok(solution) => {
+                let objective = &self.problem.objective;
+                let is_better = self.best_solution.as_ref()
+                    .is_none_or(|best| (self.fn_is_better)(objective.eval_with(&solution), objective.eval_with(best)));
+                if is_better {
+                    self.best_solution = Some(solution);
+                }
+            },
+            Err(violation) if self.start_policy == StartPolicy::Error => {
+                return Err(ResolutionError::Str(format!("Infeasible MIP start: {}", violation)));
+            },
+            Err(violation) => self.mip_start_outcome = Some(StartOutcome::Rejected(violation)),
+        }
+        Ok(())
+    }
+
+    /// Checks the start solution against the model, `Ok` if there is none
+    pub fn validate_start(&self) -> Result<(), StartViolation> {
+        match &self.best_solution {
+            Some(start) => check_solution(&self.problem, &self.constraints, &self.int_vars, start),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the start policy if the start solution does not fit the model
+    fn check_start(&mut self) -> Result<(), ResolutionError> {
+        let Err(violation) = self.validate_start() else {
+            return Ok(());
+        };
+
+        let start = self.best_solution.take().unwrap();
+        match self.start_policy {
+            StartPolicy::Error => {
+                return Err(ResolutionError::Str(format!("Infeasible start solution: {}", violation)));
+            },
+            StartPolicy::Reject => {
+                self.start_outcome = Some(StartOutcome::Rejected(violation));
+            },
+            StartPolicy::Repair => {
+                let repaired = match violation {
+                    StartViolation::WrongLength { .. } => None,
+                    _ => MipContext {
+                        problem: &self.problem,
+                        constraints: &self.constraints,
+                        int_vars: &self.int_vars,
+                        solve_lp: &self.solve_lp,
+                        incumbent: None,
+                    }.run(MipHeuristic::Rounding, &start),
+                };
+                self.best_solution = repaired.filter(|repaired|
+                    check_solution(&self.problem, &self.constraints, &self.int_vars, repaired).is_ok()
+                );
+                self.start_outcome = Some(match self.best_solution {
+                    Some(_) => StartOutcome::Repaired(violation),
+                    None => StartOutcome::Rejected(violation),
+                });
+            },
+        }
+        Ok(())
+    }
+
+    /// Improves the start solution and every new incumbent by local search
+    pub fn add_local_search(mut self, obj_vals: &[f64], method: LocalSearch) -> Self {
+        self.local_search = Some((obj_vals.to_vec(), method));
+        self
+    }
+
+    /// Stops branching after this many nodes and returns the best solution found
+    pub fn with_node_limit(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Stops branching once the time is up and returns the best solution found
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn with_branching(mut self, rule: BranchingRule) -> Self {
+        self.branching = rule;
+        self
+    }
+
+    /// Runs the heuristics at the root and then every `frequency` nodes, 0 for the root only
+    pub fn with_mip_heuristics(mut self, heuristics: &[MipHeuristic], frequency: usize) -> Self {
+        let timing = match frequency {
+            0 => HeuristicTiming::Root,
+            k => HeuristicTiming::EveryNodes(k),
+        };
+        for heuristic in heuristics {
+            self = self.add_primal_heuristic(*heuristic, timing);
+        }
+        self
+    }
+
+    pub fn add_primal_heuristic(mut self, heuristic: impl PrimalHeuristic + 'static, timing: HeuristicTiming) -> Self {
+        self.primal_heuristics.push((Box::new(heuristic), timing));
+        self
+    }
+
+    /// Searches the neighbourhoods as sub-MIPs of at most `max_nodes` nodes,
+    /// at the root and then every `frequency` nodes, 0 for the root only
+    pub fn with_lns(mut self, neighbourhoods: &[Neighbourhood], frequency: usize, max_nodes: usize) -> Self {
+        self.neighbourhoods = neighbourhoods.to_vec();
+        self.lns_frequency = frequency;
+        self.lns_nodes = max_nodes;
+        self
+    }
+
+    fn is_due(&self, frequency: usize) -> bool {
+        self.num_evals == 1 || (frequency > 0 && self.num_evals.is_multiple_of(frequency))
+    }
+
+    /// Keeps the solution if it is feasible and better than the incumbent
+    fn offer_solution(&mut self, mut solution: CustomSolution) -> bool {
+        if check_solution(&self.problem, &self.constraints, &self.int_vars, &solution).is_err() {
+            self.num_rejected += 1;
+            return false;
+        }
+
         let objective = &self.problem.objective;
+        let is_better = self.best_solution.as_ref()
+            .is_none_or(|best| (self.fn_is_better)(objective.eval_with(&solution), objective.eval_with(best)));
+        if is_better {
+            solution.num_evals = self.num_evals;
+            self.best_solution = Some(solution);
+            self.improve_incumbent();
+        }
+        is_better
+    }
 
-        let mut solver = self.problem.clone().using(highs);
-        for c in &constraints {
-            solver = solver.with(c.clone());
+    /// Solves the sub-MIPs of the neighbourhoods around the current node with a node limit
+    fn run_lns(&mut self, constraints: &[Constraint], relaxation: &CustomSolution) -> bool {
+        let mut improved = false;
+        if !self.is_due(self.lns_frequency) {
+            return improved;
         }
 
+        for neighbourhood in self.neighbourhoods.clone() {
+            let Some((problem, sub_constraints)) = restrict(
+                neighbourhood, &self.problem, constraints, &self.int_vars, relaxation, self.best_solution.as_ref()
+            ) else {
+                continue;
+            };
+
+            // The incumbent only serves as cutoff, it may lie outside of the neighbourhood
+            let mut sub = CustomSolver::new(problem);
+            sub.int_vars = self.int_vars.clone();
+            sub.solve_lp = self.solve_lp.clone();
+            sub.best_solution = self.best_solution.clone();
+            sub.max_nodes = Some(self.lns_nodes);
+            sub.solve_rec(sub_constraints);
+
+            if let Some(solution) = sub.best_solution {
+                improved |= self.offer_solution(solution);
+            }
+        }
+        improved
+    }
+
+    /// Runs the registered heuristics that are due at the current node, resp. those
+    /// waiting for a new incumbent. Returns whether the incumbent was improved.
+    fn run_primal_heuristics(&mut self, constraints: &[Constraint], relaxation: &CustomSolution, on_new_incumbent: bool) -> bool {
+        let mut heuristics = std::mem::take(&mut self.primal_heuristics);
+        let mut improved = false;
+
+        for (heuristic, timing) in heuristics.iter_mut() {
+            let is_due = match timing {
+                HeuristicTiming::Root => !on_new_incumbent && self.num_evals == 1,
+                HeuristicTiming::EveryNodes(k) => !on_new_incumbent && self.is_due(*k),
+                HeuristicTiming::OnNewIncumbent => on_new_incumbent,
+            };
+            if !is_due {
+                continue;
+            }
+
+            let context = HeuristicContext {
+                problem: &self.problem,
+                constraints,
+                int_vars: &self.int_vars,
+                relaxation,
+                incumbent: self.best_solution.as_ref(),
+                num_nodes: self.num_evals,
+                solve_lp: &self.solve_lp,
+            };
+            if let Some(solution) = heuristic.find_solution(&context) {
+                improved |= self.offer_solution(solution);
+            }
+        }
+
+        self.primal_heuristics = heuristics;
+        improved
+    }
+
+    /// Replaces the incumbent if the local search finds a better one
+    fn improve_incumbent(&mut self) {
+        let (Some((obj_vals, method)), Some(best)) = (&self.local_search, &self.best_solution) else {
+            return;
+        };
+
+        let improved = improve_solution(obj_vals, best, *method);
+        let objective = &self.problem.objective;
+        if (self.fn_is_better)(objective.eval_with(&improved), objective.eval_with(best)) {
+            self.best_solution = Some(improved);
+        }
+    }
+
+    fn solve_rec(&mut self, mut constraints: Vec<Constraint>) {
+        if self.max_nodes.is_some_and(|max| self.num_evals >= max)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return;
+        }
+        self.num_evals += 1;
+        let objective = &self.problem.objective;
+
         // Current solution found or stop
-        let Ok(cur_solution) = solver.solve() else {
+        let Ok((cur_solution, duals)) = (self.solve_lp)(self.problem.clone(), &constraints) else {
             return;
         };
         let objective_value = cur_solution.eval(objective);
 
         // Determine next integer variable
-        let next_var = self.problem.variables.iter_variables_with_def()
+        let fractional = self.problem.variables.iter_variables_with_def()
             .map(|(v, _)| v)
             .filter(|v| self.int_vars.contains(v))
-            .filter(|v| cur_solution.value(*v).fract() != 0.)
-            .reduce(|v1, v2| if cur_solution.value(v1) > cur_solution.value(v2) { v1 } else { v2 });
+            .filter(|v| (cur_solution.value(*v) - cur_solution.value(*v).round()).abs() > INT_TOL);
+        let next_var = match self.branching {
+            BranchingRule::LargestValue => fractional
+                .reduce(|v1, v2| if cur_solution.value(v1) > cur_solution.value(v2) { v1 } else { v2 }),
+            BranchingRule::MostFractional => fractional
+                .min_by(|v1, v2| {
+                    let distance = |v: &Variable| (cur_solution.value(*v).fract().abs() - 0.5).abs();
+                    distance(v1).total_cmp(&distance(v2))
+                }),
+            BranchingRule::FirstIndex => fractional.min_by_key(|v| v.index()),
+        };
         
         // Current solution worse than best -> stop
         if let Some(best_solution) = &self.best_solution {
@@ -78,10 +446,37 @@ impl CustomSolver {
         // At this point, the current solution is better
         // -> override best if no next integer variable
         let Some(next) = next_var else { 
-            self.best_solution = Some(CustomSolution::from(&cur_solution, self.problem.variables.len(), self.num_evals));
+            // Duals of the branching constraints are part of the reduced costs
+            let mut cur_solution = cur_solution;
+            cur_solution.num_evals = self.num_evals;
+            cur_solution.duals = duals.map(|duals| LpDuals::new(
+                &objective.clone().linear_coefficients().collect(),
+                &self.constraints,
+                duals[..self.constraints.len()].to_vec(),
+                &cur_solution,
+            ));
+            self.best_solution = Some(cur_solution);
+            self.improve_incumbent();
+            if !self.primal_heuristics.is_empty() {
+                let incumbent = self.best_solution.clone().unwrap();
+                self.run_primal_heuristics(&constraints, &incumbent, true);
+            }
             return;
         };
 
+        // Heuristic solutions may allow to prune the node
+        if !self.primal_heuristics.is_empty() || !self.neighbourhoods.is_empty() {
+            let mut improved = self.run_primal_heuristics(&constraints, &cur_solution, false);
+            improved |= self.run_lns(&constraints, &cur_solution);
+            if improved {
+                self.run_primal_heuristics(&constraints, &cur_solution, true);
+            }
+            let objective = &self.problem.objective;
+            if let Some(best_solution) = &self.best_solution {
+                if !(self.fn_is_better)(objective_value, objective.eval_with(best_solution)) { return; }
+            }
+        }
+
         // Prepare next recursion
         let mut constraints_lower = constraints.clone();
         constraints_lower.push(constraint!(next <= cur_solution.value(next).floor()));
@@ -98,7 +493,16 @@ impl SolverModel for CustomSolver {
     type Error = ResolutionError;
 
     fn solve(mut self) -> Result<Self::Solution, Self::Error> {
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        self.check_start()?;
+        self.apply_mip_start()?;
+        self.improve_incumbent();
         self.solve_rec(self.constraints.clone());
+        if let Some(best) = &mut self.best_solution {
+            best.start = self.start_outcome.take();
+            best.mip_start = self.mip_start_outcome.take();
+            best.num_rejected = self.num_rejected;
+        }
         let solution = self.best_solution.ok_or(
             Err(ResolutionError::Other("No Solution found"))
         );
@@ -118,11 +522,22 @@ impl SolverModel for CustomSolver {
 
 #[cfg(test)]
 mod tests {
-    use good_lp::{ProblemVariables, variable, constraint, SolverModel, Solution, Variable, Expression};
+    use good_lp::{ProblemVariables, variable, constraint, SolverModel, Solution, Variable, Expression, ResolutionError};
 
     use crate::heuristics::StartHeuristic;
+    use crate::local_search::LocalSearch;
+    use crate::metaheuristics::TabuSearch;
+    use crate::mip_heuristics::MipHeuristic;
+    use crate::solution::CustomSolution;
+    use crate::validation::{StartPolicy, StartViolation, StartOutcome};
+    use std::collections::HashMap;
+    use crate::lns::Neighbourhood;
+    use crate::primal_heuristic::{PrimalHeuristic, HeuristicContext, HeuristicTiming};
+    use std::{rc::Rc, cell::Cell};
+
+    use std::time::Duration;
 
-    use super::CustomSolver;
+    use super::{CustomSolver, LpBackend, BranchingRule};
 
     #[test]
     fn test_setup() {
@@ -215,6 +630,152 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mip_heuristics() {
+        let weights_obj = [0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+        let heuristics = [
+            MipHeuristic::Rounding,
+            MipHeuristic::FractionalDiving,
+            MipHeuristic::CoefficientDiving,
+            MipHeuristic::GuidedDiving,
+            MipHeuristic::FeasibilityPump,
+        ];
+
+        for frequency in [0, 2] {
+            let mut problem = ProblemVariables::new();
+            let vars: Vec<Variable> = (0..weights_obj.len())
+                .map(|_| problem.add(variable().integer().min(0).max(1)))
+                .collect();
+
+            let obj: Expression = weights_obj.iter()
+                .zip(&vars)
+                .map(|(w, v)| *w * *v)
+                .sum();
+
+            let constr_lhs: Expression = weights_constr.iter()
+                .zip(&vars)
+                .map(|(w, v)| *w * *v)
+                .sum();
+
+            let solution = problem.maximise(obj.clone()).using(CustomSolver::new)
+                .with(constraint!(constr_lhs <= 40))
+                .with_mip_heuristics(&heuristics, frequency)
+                .solve().unwrap();
+
+            assert!((obj.eval_with(&solution) - 0.45).abs() < 1e-6);
+        }
+    }
+
+    /// Proposes the given solution and counts its calls
+    struct FixedHeuristic {
+        values: Vec<f64>,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl PrimalHeuristic for FixedHeuristic {
+        fn find_solution(&mut self, _: &HeuristicContext) -> Option<CustomSolution> {
+            self.calls.set(self.calls.get() + 1);
+            Some(CustomSolution::new(self.values.clone()))
+        }
+    }
+
+    #[test]
+    fn test_primal_heuristic() {
+        let weights_obj = [0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+        let optimum = vec![1., 0., 1., 1., 1., 1., 0., 1.];
+        let infeasible = vec![1.; 8];
+
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+
+        let obj: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        let constr_lhs: Expression = weights_constr.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        let solve = |heuristics: Vec<(Vec<f64>, HeuristicTiming)>| {
+            let mut solver = problem.clone().maximise(obj.clone()).using(CustomSolver::new)
+                .with(constraint!(constr_lhs.clone() <= 40));
+            let mut counters = Vec::new();
+            for (values, timing) in heuristics {
+                let calls = Rc::new(Cell::new(0));
+                solver = solver.add_primal_heuristic(FixedHeuristic{ values, calls: calls.clone() }, timing);
+                counters.push(calls);
+            }
+            let solution = solver.solve().unwrap();
+            (solution, counters.iter().map(|c| c.get()).collect::<Vec<_>>())
+        };
+
+        // The optimum is found at the root, the bound of 0.457 still requires branching
+        let (solution, calls) = solve(vec![(optimum.clone(), HeuristicTiming::Root)]);
+        assert!((obj.clone().eval_with(&solution) - 0.45).abs() < 1e-6);
+        assert_eq!(solution.num_evals, 1);
+        assert_eq!(calls, vec![1]);
+        assert_eq!(solution.num_rejected, 0);
+
+        // Infeasible proposals are ignored
+        let (solution, calls) = solve(vec![
+            (infeasible.clone(), HeuristicTiming::EveryNodes(1)),
+            (optimum, HeuristicTiming::OnNewIncumbent),
+        ]);
+        assert!((obj.clone().eval_with(&solution) - 0.45).abs() < 1e-6);
+        assert!(calls[0] > 1);
+        assert_eq!(solution.num_rejected, calls[0]);
+        assert_eq!(calls[1], 1);
+
+        let (_, calls) = solve(vec![(infeasible, HeuristicTiming::OnNewIncumbent)]);
+        assert!(calls[0] >= 1);
+    }
+
+    #[test]
+    fn test_lns() {
+        let weights_obj = [0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+
+        let obj: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        let constr_lhs: Expression = weights_constr.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        // Only the root is solved, starting from the empty knapsack
+        let solver = |neighbourhoods: &[Neighbourhood]| problem.clone()
+            .maximise(obj.clone()).using(CustomSolver::new)
+            .with(constraint!(constr_lhs.clone() <= 40))
+            .with_mip_start(vars.iter().map(|v| (*v, 0.)).collect())
+            .with_node_limit(1)
+            .with_lns(neighbourhoods, 0, 100);
+
+        let solution = solver(&[]).solve().unwrap();
+        assert!(obj.clone().eval_with(&solution) < 1e-6);
+
+        for neighbourhood in [Neighbourhood::Rens, Neighbourhood::Rins] {
+            let solution = solver(&[neighbourhood]).solve().unwrap();
+            assert!(obj.clone().eval_with(&solution) > 0.4);
+        }
+
+        let solution = solver(&[Neighbourhood::LocalBranching(8)]).solve().unwrap();
+        assert!((obj.clone().eval_with(&solution) - 0.45).abs() < 1e-6);
+    }
+
     #[test]
     fn test_example_abs() {
         let mut problem = ProblemVariables::new();
@@ -272,12 +833,12 @@ mod tests {
             vars.len() == vars.iter().enumerate()
                 .map(|(i, v)| solution.value(*v) - ((i == 0) as i32 as f64))
                 .map(|d| (d < 10e-6) as usize)
-                .sum()
+                .sum::<usize>()
             ||
             vars.len() == vars.iter().enumerate()
                 .map(|(i, v)| solution.value(*v) - ((i != 0) as i32 as f64))
                 .map(|d| (d < 10e-6) as usize)
-                .sum()
+                .sum::<usize>()
         );
     }
 
@@ -330,4 +891,318 @@ mod tests {
 
         assert_eq!(solution.num_evals, 1);
     }
+
+    #[test]
+    fn test_local_search() {
+        // Greedy alone has a difference of 4, a swap makes it perfect and the root is pruned
+        let weights_obj = vec![8., 7., 6., 5., 4.];
+
+        let mut problem = ProblemVariables::new();
+
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let diff = problem.add(variable());
+
+        let constr_weights: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| (2 * *v - 1) * *w)
+            .sum();
+
+        let solution = problem.minimise(diff).using(CustomSolver::new)
+            .with(constraint!(constr_weights.clone() <= diff))
+            .with(constraint!(-constr_weights <= diff))
+            .add_heuristic(&weights_obj, StartHeuristic::Greedy)
+            .add_local_search(&weights_obj, LocalSearch::Swaps)
+            .solve().unwrap();
+
+        assert_eq!(solution.num_evals, 0);
+        assert!(solution.value(diff) < 1e-6);
+    }
+
+    #[test]
+    fn test_start_wrong_layout() {
+        // Partition heuristics do not fit the knapsack
+        let weights_obj = vec![0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+
+        for policy in [StartPolicy::Reject, StartPolicy::Repair, StartPolicy::Error] {
+            let mut problem = ProblemVariables::new();
+            let vars: Vec<Variable> = (0..weights_obj.len())
+                .map(|_| problem.add(variable().integer().min(0).max(1)))
+                .collect();
+
+            let obj: Expression = weights_obj.iter()
+                .zip(&vars)
+                .map(|(w, v)| *w * *v)
+                .sum();
+
+            let constr_lhs: Expression = weights_constr.iter()
+                .zip(&vars)
+                .map(|(w, v)| *w * *v)
+                .sum();
+
+            let solver = problem.maximise(obj.clone()).using(CustomSolver::new)
+                .with(constraint!(constr_lhs <= 40))
+                .add_heuristic(&weights_obj, StartHeuristic::Greedy)
+                .with_start_policy(policy);
+            assert_eq!(solver.validate_start(), Err(StartViolation::WrongLength { expected: 8, found: 9 }));
+
+            let violation = StartViolation::WrongLength { expected: 8, found: 9 };
+            match policy {
+                StartPolicy::Error => assert!(matches!(solver.solve(),
+                    Err(ResolutionError::Str(msg)) if msg.ends_with(&violation.to_string()))),
+                _ => {
+                    let solution = solver.solve().unwrap();
+                    assert!((obj.eval_with(&solution) - 0.45).abs() < 1e-6);
+                    assert_eq!(solution.start, Some(StartOutcome::Rejected(violation)));
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_start_repair() {
+        let weights_obj = [5., 3., 2.];
+
+        let mut problem = ProblemVariables::new();
+
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let diff = problem.add(variable());
+
+        let constr_weights: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| (2 * *v - 1) * *w)
+            .sum();
+
+        let mut solver = problem.minimise(diff).using(CustomSolver::new)
+            .with(constraint!(constr_weights.clone() <= diff))
+            .with(constraint!(-constr_weights <= diff));
+
+        // Difference of {5, 3} vs {2} is 6, not 0
+        solver.best_solution = Some(CustomSolution::new(vec![1., 1., 0., 0.]));
+        assert_eq!(solver.validate_start(), Err(StartViolation::Constraint { index: 0, violation: 6. }));
+
+        solver.check_start().unwrap();
+        let start = solver.best_solution.as_ref().unwrap();
+        assert!((start.value(diff) - 6.).abs() < 1e-6);
+        assert_eq!(solver.validate_start(), Ok(()));
+
+        let solution = solver.solve().unwrap();
+        assert!(solution.value(diff) < 1e-6);
+        assert_eq!(solution.start, Some(StartOutcome::Repaired(StartViolation::Constraint { index: 0, violation: 6. })));
+    }
+
+    #[test]
+    fn test_start_nan() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().integer().min(0).max(1));
+        let y = problem.add(variable().integer().min(0).max(1));
+
+        let solver = |policy: StartPolicy| {
+            let mut solver = problem.clone().maximise(x + y).using(CustomSolver::new)
+                .with(constraint!(x + y <= 1))
+                .with_start_policy(policy);
+            solver.best_solution = Some(CustomSolution::new(vec![f64::NAN, 1.]));
+            solver
+        };
+
+        assert!(matches!(solver(StartPolicy::Error).solve(),
+            Err(ResolutionError::Str(msg)) if msg.starts_with("Infeasible start solution")));
+        let solution = solver(StartPolicy::Reject).solve().unwrap();
+        assert!(matches!(solution.start, Some(StartOutcome::Rejected(StartViolation::Bound { variable: 0, .. }))));
+        assert!(solution.values().iter().all(|v| v.is_finite()));
+        assert!((solution.value(x) + solution.value(y) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mip_start() {
+        let weights_obj = [5., 3., 2.];
+
+        let mut problem = ProblemVariables::new();
+
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let diff = problem.add(variable());
+
+        let constr_weights: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| (2 * *v - 1) * *w)
+            .sum();
+
+        let solver = |start: HashMap<Variable, f64>, policy: StartPolicy| problem.clone()
+            .minimise(diff).using(CustomSolver::new)
+            .with(constraint!(constr_weights.clone() <= diff))
+            .with(constraint!(-constr_weights.clone() <= diff))
+            .with_start_policy(policy)
+            .with_mip_start(start);
+
+        // Optimal complete start, the root relaxation cannot improve on it
+        let start: HashMap<_, _> = [(vars[0], 1.), (vars[1], 0.), (vars[2], 0.), (diff, 0.)].into_iter().collect();
+        let solution = solver(start, StartPolicy::Error).solve().unwrap();
+        assert_eq!(solution.num_evals, 0);
+        assert!(solution.value(diff) < 1e-6);
+
+        // Partial start, the difference is completed by the relaxation
+        let start: HashMap<_, _> = [(vars[0], 0.), (vars[1], 0.), (vars[2], 1.)].into_iter().collect();
+        let mut solver1 = solver(start, StartPolicy::Error);
+        solver1.apply_mip_start().unwrap();
+        assert!((solver1.best_solution.as_ref().unwrap().value(diff) - 6.).abs() < 1e-6);
+        assert!(solver1.solve().unwrap().value(diff) < 1e-6);
+
+        // Fractional values are only rounded when repairing
+        let start: HashMap<_, _> = [(vars[0], 0.9)].into_iter().collect();
+        assert!(matches!(solver(start.clone(), StartPolicy::Error).solve(),
+            Err(ResolutionError::Str(msg)) if msg.starts_with("Infeasible MIP start")));
+        let solution = solver(start.clone(), StartPolicy::Reject).solve().unwrap();
+        assert!(matches!(solution.mip_start, Some(StartOutcome::Rejected(_))));
+        assert!(solution.value(diff) < 1e-6);
+        let mut solver2 = solver(start, StartPolicy::Repair);
+        solver2.apply_mip_start().unwrap();
+        assert!((solver2.best_solution.as_ref().unwrap().value(vars[0]) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_metaheuristic_start() {
+        let weights_obj = vec![8., 24., 26., 13., 14., 22., 6., 12.];
+
+        let mut problem = ProblemVariables::new();
+
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let diff = problem.add(variable());
+
+        let constr_weights: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| (2 * *v - 1) * *w)
+            .sum();
+
+        let solution = problem.minimise(diff).using(CustomSolver::new)
+            .with(constraint!(constr_weights.clone() <= diff))
+            .with(constraint!(-constr_weights <= diff))
+            .add_heuristic(&weights_obj, StartHeuristic::TabuSearch(TabuSearch::new(42)))
+            .solve().unwrap();
+
+        assert!((solution.value(diff) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_native_backend() {
+        let mut problem = ProblemVariables::new();
+        let x1 = problem.add(variable().integer().min(0));
+        let x2 = problem.add(variable().integer().min(0));
+        let solution = problem.maximise(5*x1 + 8*x2)
+            .using(|p| CustomSolver::new(p).with_backend(LpBackend::Native))
+            .with(constraint!(x1 + x2 <= 6))
+            .with(constraint!(5*x1 + 9*x2 <= 45))
+            .solve().unwrap();
+
+        assert!((solution.value(x1) - 0.).abs() < 10e-6);
+        assert!((solution.value(x2) - 5.).abs() < 10e-6);
+    }
+
+    #[test]
+    fn test_native_backend_knapsack() {
+        let weights_obj = [0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+
+        let obj: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        let constr_lhs: Expression = weights_constr.iter()
+            .zip(&vars)
+            .map(|(w, v)| *w * *v)
+            .sum();
+
+        let solution = problem.maximise(obj.clone())
+            .using(|p| CustomSolver::new(p).with_backend(LpBackend::Native))
+            .with(constraint!(constr_lhs <= 40))
+            .solve().unwrap();
+
+        assert!((solution.eval(&obj) - 0.45).abs() < 10e-6);
+    }
+
+    #[test]
+    fn test_branching_rules() {
+        let weights_obj = [0.1, 0.04, 0.07, 0.12, 0.08, 0.03, 0.04, 0.05];
+        let weights_constr = [9., 7., 5., 8., 5., 3., 7., 10.];
+
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let obj: Expression = weights_obj.iter().zip(&vars).map(|(w, v)| *w * *v).sum();
+        let constr_lhs: Expression = weights_constr.iter().zip(&vars).map(|(w, v)| *w * *v).sum();
+        let problem = problem.maximise(obj.clone());
+
+        for rule in [BranchingRule::LargestValue, BranchingRule::MostFractional, BranchingRule::FirstIndex] {
+            let solution = problem.clone()
+                .using(|p| CustomSolver::new(p).with_branching(rule))
+                .with(constraint!(constr_lhs.clone() <= 40))
+                .solve().unwrap();
+            assert!((solution.eval(&obj) - 0.45).abs() < 10e-6);
+        }
+
+        // No node is solved, so there is no solution
+        let result = problem.using(|p| CustomSolver::new(p).with_time_limit(Duration::ZERO))
+            .with(constraint!(constr_lhs <= 40))
+            .solve();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_native_backend_equal_split() {
+        let weights_obj = [3., 3., 2., 2., 2.];
+
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..weights_obj.len())
+            .map(|_| problem.add(variable().integer().min(0).max(1)))
+            .collect();
+        let diff = problem.add(variable());
+
+        let constr_weights: Expression = weights_obj.iter()
+            .zip(&vars)
+            .map(|(w, v)| (2 * *v - 1) * *w)
+            .sum();
+
+        let solution = problem.minimise(diff)
+            .using(|p| CustomSolver::new(p).with_lp_solver(crate::solver::my_solver))
+            .with(constraint!(constr_weights.clone() <= diff))
+            .with(constraint!(-constr_weights <= diff))
+            .solve().unwrap();
+
+        assert!(solution.value(diff).abs() < 10e-6);
+    }
+
+    #[test]
+    fn test_duals() {
+        for backend in [LpBackend::Highs, LpBackend::Native] {
+            let mut problem = ProblemVariables::new();
+            let a = problem.add(variable().integer().min(0));
+            let b = problem.add(variable().integer().min(0));
+
+            let mut model = problem.maximise(7*a + 6*b)
+                .using(|p| CustomSolver::new(p).with_backend(backend));
+            let c1 = model.add_constraint(constraint!(2*a + 4*b <= 16));
+            let c2 = model.add_constraint(constraint!(3*a + 2*b <= 12));
+            let solution = model.solve().unwrap();
+
+            let duals = solution.duals.unwrap();
+            assert!((duals.dual(&c1) - 0.5).abs() < 1e-6);
+            assert!((duals.dual(&c2) - 2.).abs() < 1e-6);
+            assert!(duals.slack(&c1).abs() < 1e-6);
+            assert!(duals.reduced_cost(a).abs() < 1e-6);
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use serde::Serialize;
+use crate::validation::StartViolation;
+
+/// Relative tolerance of objective values, absolute tolerance of variable values
+pub const TOL: f64 = 1e-6;
+
+/// How a solution with the same objective as the reference relates to it
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum Equivalence {
+    Identical,
+    /// Same solution up to a symmetry of the model, e.g. swapped sides of a partition
+    Symmetric,
+    /// Different solution with the same objective
+    AlternativeOptimum,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Discrepancy {
+    Infeasible(StartViolation),
+    Objective { expected: f64, found: f64 },
+}
+
+/// Outcome of checking a solution against the model and the reference solution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Verification {
+    /// Feasible solution the others are compared to
+    Reference,
+    Equivalent(Equivalence),
+    Discrepant(Vec<Discrepancy>),
+}
+
+pub fn objectives_equal(expected: f64, found: f64) -> bool {
+    (expected - found).abs() <= TOL * expected.abs().max(found.abs()).max(1.)
+}
+
+pub fn values_equal(reference: &[f64], other: &[f64]) -> bool {
+    reference.len() == other.len() && reference.iter().zip(other).all(|(a, b)| (a - b).abs() <= TOL)
+}
+
+/// Classifies a solution with the objective `found`, `is_symmetric` tells whether
+/// differing values describe the same solution up to a symmetry
+pub fn compare(
+    reference: &[f64], expected: f64, other: &[f64], found: f64, is_symmetric: impl Fn(&[f64], &[f64]) -> bool
+) -> Result<Equivalence, Discrepancy> {
+    if !objectives_equal(expected, found) {
+        Err(Discrepancy::Objective { expected, found })
+    } else if values_equal(reference, other) {
+        Ok(Equivalence::Identical)
+    } else if is_symmetric(reference, other) {
+        Ok(Equivalence::Symmetric)
+    } else {
+        Ok(Equivalence::AlternativeOptimum)
+    }
+}
+
+/// Whether both subset labellings describe the same partition, the labels are renumbered
+/// in the order of the first item of each subset
+pub fn same_partition(reference: &[usize], other: &[usize]) -> bool {
+    let canonical = |subsets: &[usize]| {
+        let mut labels = Vec::new();
+        subsets.iter()
+            .map(|s| match labels.iter().position(|l| l == s) {
+                Some(pos) => pos,
+                None => {
+                    labels.push(*s);
+                    labels.len() - 1
+                },
+            })
+            .collect::<Vec<_>>()
+    };
+    reference.len() == other.len() && canonical(reference) == canonical(other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, same_partition, objectives_equal, Discrepancy, Equivalence};
+
+    #[test]
+    fn test_compare() {
+        let mirrored = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(x, y)| (x + y - 1.).abs() < 1e-6);
+
+        assert_eq!(compare(&[1., 0.], 2., &[1., 1e-9], 2. + 1e-9, mirrored), Ok(Equivalence::Identical));
+        assert_eq!(compare(&[1., 0.], 2., &[0., 1.], 2., mirrored), Ok(Equivalence::Symmetric));
+        assert_eq!(compare(&[1., 0.], 2., &[1., 1.], 2., mirrored), Ok(Equivalence::AlternativeOptimum));
+        assert_eq!(compare(&[1., 0.], 2., &[1., 0.], 3., mirrored), Err(Discrepancy::Objective { expected: 2., found: 3. }));
+
+        assert!(objectives_equal(1e8, 1e8 + 1.));
+        assert!(!objectives_equal(0., 1e-5));
+    }
+
+    #[test]
+    fn test_same_partition() {
+        assert!(same_partition(&[2, 0, 1, 1, 0], &[0, 1, 2, 2, 1]));
+        assert!(same_partition(&[1, 0, 0], &[0, 1, 1]));
+        assert!(!same_partition(&[0, 1, 1], &[0, 1, 0]));
+        assert!(!same_partition(&[0, 1], &[0, 1, 1]));
+    }
+}
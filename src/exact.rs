@@ -0,0 +1,368 @@
+use good_lp::ResolutionError;
+use num_rational::BigRational;
+use num_traits::{Zero, Signed};
+
+/// Arithmetic used by `MySolver`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Floating-point simplex only
+    Float,
+    /// Solve the whole problem with rationals
+    Exact,
+    /// Solve with floats, then verify the final basis with rationals and repair it if necessary
+    Verified,
+}
+
+/// Exact optimum of the tableau
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactResult {
+    pub values: Vec<BigRational>,
+    pub objective: BigRational,
+    /// Dual value of every row, see `SimplexResult::duals`
+    pub duals: Vec<BigRational>,
+    /// Pivots performed in exact arithmetic, 0 if a given start basis was already optimal
+    pub num_pivots: usize,
+}
+
+/// Converts a float exactly, every finite float is a dyadic rational
+pub fn to_rational(value: f64) -> BigRational {
+    BigRational::from_float(value).expect("Coefficients must be finite")
+}
+
+/// Simplex tableau over rationals, always uses Bland's rule since there is no round-off
+struct ExactTableau {
+    rows: Vec<Vec<BigRational>>,
+    reduced_costs: Vec<BigRational>,
+    rhs: Vec<BigRational>,
+    basis: Vec<Option<usize>>,
+    inverse: Vec<Vec<BigRational>>,
+    objective: Vec<BigRational>,
+    num_cols: usize,
+}
+
+impl ExactTableau {
+    /// Same column layout as the floating-point `Tableau` in `algorithm.rs`
+    fn from_columns(table: &[Vec<f64>], basic_var_cols: &[usize]) -> Self {
+        let columns = &table[1..table.len()-1];
+        let rhs_col = &table[table.len()-1];
+        let num_rows = rhs_col.len() - 1;
+
+        let mut rows: Vec<Vec<BigRational>> = (1..=num_rows)
+            .map(|i| columns.iter().map(|col| to_rational(col[i])).collect())
+            .collect();
+        let mut rhs: Vec<BigRational> = rhs_col[1..].iter().map(|b| to_rational(*b)).collect();
+
+        let mut inverse = vec![vec![BigRational::zero(); num_rows]; num_rows];
+        for (i, (row, b)) in rows.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            inverse[i][i] = BigRational::from_integer(1.into());
+            if b.is_negative() {
+                row.iter_mut().for_each(|a| *a = -a.clone());
+                *b = -b.clone();
+                inverse[i][i] = BigRational::from_integer((-1).into());
+            }
+        }
+
+        let mut basis = vec![None; num_rows];
+        for &col in basic_var_cols {
+            let unit_row = (0..num_rows).find(|&i| rows[i][col] == BigRational::from_integer(1.into()));
+            let Some(i) = unit_row else { continue };
+            if basis[i].is_none() && (0..num_rows).all(|k| k == i || rows[k][col].is_zero()) {
+                basis[i] = Some(col);
+            }
+        }
+
+        let objective: Vec<BigRational> = columns.iter().map(|col| to_rational(col[0])).collect();
+        ExactTableau {
+            rows,
+            reduced_costs: vec![BigRational::zero(); columns.len()],
+            rhs,
+            basis,
+            inverse,
+            objective,
+            num_cols: columns.len(),
+        }
+    }
+
+    fn pivot(&mut self, row: usize, col: usize) {
+        let factor = self.rows[row][col].clone();
+        self.rows[row].iter_mut().for_each(|a| *a /= &factor);
+        self.rhs[row] /= &factor;
+        self.inverse[row].iter_mut().for_each(|a| *a /= &factor);
+
+        let pivot_row = self.rows[row].clone();
+        let pivot_rhs = self.rhs[row].clone();
+        let pivot_inverse = self.inverse[row].clone();
+        for i in 0..self.rows.len() {
+            let a = self.rows[i][col].clone();
+            if i == row || a.is_zero() { continue; }
+            self.rows[i].iter_mut().zip(&pivot_row).for_each(|(x, p)| *x -= &a * p);
+            self.rhs[i] -= &a * &pivot_rhs;
+            self.inverse[i].iter_mut().zip(&pivot_inverse).for_each(|(x, p)| *x -= &a * p);
+        }
+
+        let d = self.reduced_costs[col].clone();
+        if !d.is_zero() {
+            self.reduced_costs.iter_mut().zip(&pivot_row).for_each(|(x, p)| *x -= &d * p);
+        }
+        self.basis[row] = Some(col);
+    }
+
+    /// Sets the reduced costs of `costs` with respect to the current basis
+    fn price(&mut self, costs: Vec<BigRational>) {
+        self.reduced_costs = costs;
+        for i in 0..self.rows.len() {
+            let Some(b) = self.basis[i] else { continue };
+            let c = self.reduced_costs[b].clone();
+            if c.is_zero() { continue; }
+            for j in 0..self.reduced_costs.len() {
+                let delta = &c * &self.rows[i][j];
+                self.reduced_costs[j] -= delta;
+            }
+        }
+    }
+
+    /// Primal simplex with Bland's rule on the first `num_cols` columns
+    fn optimize(&mut self, num_cols: usize, num_pivots: &mut usize) -> Result<(), ResolutionError> {
+        loop {
+            let Some(col) = (0..num_cols).find(|&j| self.reduced_costs[j].is_positive()) else {
+                return Ok(());
+            };
+
+            let mut leaving: Option<(usize, BigRational)> = None;
+            for i in 0..self.rows.len() {
+                if !self.rows[i][col].is_positive() { continue; }
+                let ratio = &self.rhs[i] / &self.rows[i][col];
+                let better = match &leaving {
+                    None => true,
+                    Some((k, best)) => ratio < *best || (ratio == *best && self.basis[i] < self.basis[*k]),
+                };
+                if better { leaving = Some((i, ratio)); }
+            }
+            let Some((row, _)) = leaving else {
+                return Err(ResolutionError::Unbounded);
+            };
+
+            self.pivot(row, col);
+            *num_pivots += 1;
+        }
+    }
+
+    /// Adds artificial columns for rows without basic variable and minimizes their sum
+    fn find_feasible_basis(&mut self, num_pivots: &mut usize) -> Result<(), ResolutionError> {
+        let artificial_rows: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| self.basis[i].is_none())
+            .collect();
+
+        if !artificial_rows.is_empty() {
+            for (k, &i) in artificial_rows.iter().enumerate() {
+                for row in self.rows.iter_mut() {
+                    row.push(BigRational::zero());
+                }
+                self.rows[i][self.num_cols + k] = BigRational::from_integer(1.into());
+                self.basis[i] = Some(self.num_cols + k);
+            }
+            let num_total = self.num_cols + artificial_rows.len();
+            let mut costs = vec![BigRational::zero(); num_total];
+            costs[self.num_cols..].iter_mut().for_each(|c| *c = BigRational::from_integer((-1).into()));
+            self.price(costs);
+
+            self.optimize(num_total, num_pivots)?;
+
+            if (0..self.rows.len()).any(|i| self.is_artificial(i) && !self.rhs[i].is_zero()) {
+                return Err(ResolutionError::Infeasible);
+            }
+        }
+        self.remove_artificials();
+        Ok(())
+    }
+
+    fn is_artificial(&self, row: usize) -> bool {
+        self.basis[row].is_none_or(|b| b >= self.num_cols)
+    }
+
+    /// Pivots artificial variables out of the basis and removes redundant rows
+    fn remove_artificials(&mut self) {
+        let mut redundant = Vec::new();
+        for i in 0..self.rows.len() {
+            if !self.is_artificial(i) { continue; }
+            match (0..self.num_cols).find(|&j| !self.rows[i][j].is_zero()) {
+                Some(j) => self.pivot(i, j),
+                None => redundant.push(i),
+            }
+        }
+        for i in redundant.into_iter().rev() {
+            self.rows.remove(i);
+            self.rhs.remove(i);
+            self.basis.remove(i);
+            self.inverse.remove(i);
+        }
+        for row in self.rows.iter_mut() {
+            row.truncate(self.num_cols);
+        }
+    }
+
+    /// Pivots the given columns into the basis, returns false if they are not a feasible basis
+    fn install_basis(&mut self, basis: &[usize]) -> bool {
+        for &col in basis {
+            if self.basis.contains(&Some(col)) { continue; }
+            let row = (0..self.rows.len())
+                .filter(|&i| !self.rows[i][col].is_zero())
+                .find(|&i| self.basis[i].is_none_or(|b| !basis.contains(&b)));
+            let Some(row) = row else { return false };
+            self.pivot(row, col);
+        }
+
+        if self.rhs.iter().any(|b| b.is_negative()) { return false; }
+        if (0..self.rows.len()).any(|i| self.basis[i].is_none() && !self.rhs[i].is_zero()) { return false; }
+
+        // Remaining rows without basic variable are at zero and can be cleaned up like in phase one
+        for i in 0..self.rows.len() {
+            if self.basis[i].is_some() { continue; }
+            for row in self.rows.iter_mut() {
+                row.push(BigRational::zero());
+            }
+            let k = self.rows[i].len() - 1;
+            self.rows[i][k] = BigRational::from_integer(1.into());
+            self.basis[i] = Some(k);
+        }
+        self.remove_artificials();
+        true
+    }
+
+    fn result(&self, num_pivots: usize) -> ExactResult {
+        let mut values = vec![BigRational::zero(); self.num_cols];
+        for (i, b) in self.basis.iter().enumerate() {
+            if let Some(b) = b { values[*b] = self.rhs[i].clone(); }
+        }
+        let objective = values.iter().zip(&self.objective)
+            .map(|(x, c)| x * c)
+            .fold(BigRational::zero(), |acc, v| acc + v);
+
+        let mut duals = vec![BigRational::zero(); self.inverse.first().map_or(0, |row| row.len())];
+        for (i, b) in self.basis.iter().enumerate() {
+            let Some(b) = b else { continue };
+            let c = &self.objective[*b];
+            if c.is_zero() { continue; }
+            duals.iter_mut().zip(&self.inverse[i]).for_each(|(y, v)| *y += c * v);
+        }
+
+        ExactResult { values, objective, duals, num_pivots }
+    }
+}
+
+/// Solves the tableau in exact arithmetic.
+/// If `start_basis` is given (e.g. the basis of the floating-point simplex), it is installed first
+/// and only repaired by further pivots, falling back to phase one if it is infeasible.
+pub fn solve_exact(table: &[Vec<f64>], basic_var_cols: &[usize], start_basis: Option<&[usize]>) -> Result<ExactResult, ResolutionError> {
+    if let Some(start_basis) = start_basis {
+        let mut tableau = ExactTableau::from_columns(table, basic_var_cols);
+        if tableau.install_basis(start_basis) {
+            let mut num_pivots = 0;
+            tableau.price(tableau.objective.clone());
+            tableau.optimize(tableau.num_cols, &mut num_pivots)?;
+            return Ok(tableau.result(num_pivots));
+        }
+    }
+
+    let mut tableau = ExactTableau::from_columns(table, basic_var_cols);
+    let mut num_pivots = 0;
+    tableau.find_feasible_basis(&mut num_pivots)?;
+    tableau.price(tableau.objective.clone());
+    tableau.optimize(tableau.num_cols, &mut num_pivots)?;
+    Ok(tableau.result(num_pivots))
+}
+
+#[cfg(test)]
+mod tests {
+    use good_lp::ResolutionError;
+    use num_rational::BigRational;
+    use super::{solve_exact, to_rational};
+    use crate::algorithm::{solve_simplex_with, SimplexOptions};
+
+    fn ratio(n: i64, d: i64) -> BigRational {
+        BigRational::new(n.into(), d.into())
+    }
+
+    #[test]
+    fn test_exact_simple() {
+        let table = vec![
+            vec![0.; 3],
+            vec![7., 2., 3.],
+            vec![6., 4., 2.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 16., 12.],
+        ];
+        let result = solve_exact(&table, &[2, 3], None).unwrap();
+        assert_eq!(result.values[0], ratio(2, 1));
+        assert_eq!(result.values[1], ratio(3, 1));
+        assert_eq!(result.objective, ratio(32, 1));
+        assert_eq!(result.duals, vec![ratio(1, 2), ratio(2, 1)]);
+    }
+
+    #[test]
+    fn test_exact_fractions() {
+        // max x1 + x2, 3 x1 + x2 <= 1, x1 + 3 x2 <= 1 -> x1 = x2 = 1/4
+        let table = vec![
+            vec![0.; 3],
+            vec![1., 3., 1.],
+            vec![1., 1., 3.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 1., 1.],
+        ];
+        let result = solve_exact(&table, &[2, 3], None).unwrap();
+        assert_eq!(result.values[0], ratio(1, 4));
+        assert_eq!(result.values[1], ratio(1, 4));
+        assert_eq!(result.objective, ratio(1, 2));
+    }
+
+    #[test]
+    fn test_exact_infeasible() {
+        let table = vec![
+            vec![0.; 3],
+            vec![1., 1., -1.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 1., -2.],
+        ];
+        assert_eq!(solve_exact(&table, &[1, 2], None), Err(ResolutionError::Infeasible));
+    }
+
+    #[test]
+    fn test_verify_float_basis() {
+        let table = vec![
+            vec![0.; 3],
+            vec![0.1, 0.3, 0.1],
+            vec![0.1, 0.1, 0.3],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 0.1, 0.1],
+        ];
+        let float = solve_simplex_with(&table, &[2, 3], &SimplexOptions::default()).unwrap();
+        let result = solve_exact(&table, &[2, 3], Some(&float.basis)).unwrap();
+        assert_eq!(result.num_pivots, 0);
+        assert_eq!(result.values[0], result.values[1]);
+        assert_eq!(result.objective, to_rational(0.1) * &result.values[0] * ratio(2, 1));
+    }
+
+    #[test]
+    fn test_repair_basis() {
+        let table = vec![
+            vec![0.; 3],
+            vec![7., 2., 3.],
+            vec![6., 4., 2.],
+            vec![0., 1., 0.],
+            vec![0., 0., 1.],
+            vec![0., 16., 12.],
+        ];
+        // Slack basis is feasible but not optimal
+        let result = solve_exact(&table, &[2, 3], Some(&[2, 3])).unwrap();
+        assert!(result.num_pivots > 0);
+        assert_eq!(result.objective, ratio(32, 1));
+
+        // Basis {x1, s2} is infeasible (x1 = 8 violates the second row) -> solved from scratch
+        let result = solve_exact(&table, &[2, 3], Some(&[0, 3])).unwrap();
+        assert_eq!(result.objective, ratio(32, 1));
+    }
+}
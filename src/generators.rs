@@ -0,0 +1,290 @@
+use rand::rngs::StdRng;
+use serde::{Serialize, Deserialize};
+use crate::weight_functions::*;
+
+/// Source of problem weights, described by a serialisable spec
+pub trait WeightGenerator {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64>;
+    fn spec(&self) -> GeneratorSpec;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqualSpec {
+    pub num_weights: usize,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RandomSpec {
+    pub num_weights: usize,
+    pub min_value: f64,
+    pub max_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinearSpec {
+    pub num_weights: usize,
+    pub min_value: f64,
+    pub max_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegerSpec {
+    pub num_weights: usize,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseTransitionSpec {
+    pub num_weights: usize,
+    pub kappa: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerfectPartitionSpec {
+    pub num_weights: usize,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoPerfectPartitionSpec {
+    pub num_weights: usize,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TruncatedNormalSpec {
+    pub num_weights: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min_value: f64,
+    pub max_value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExponentialSpec {
+    pub num_weights: usize,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParetoSpec {
+    pub num_weights: usize,
+    pub scale: f64,
+    pub shape: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusteredSpec {
+    pub num_weights: usize,
+    pub centers: Vec<f64>,
+    pub std_dev: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OneGiantSpec {
+    pub num_weights: usize,
+    pub giant_value: f64,
+    pub min_value: f64,
+    pub max_value: f64,
+}
+
+/// Instance file and how its weights were generated, if known
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceSpec {
+    pub name: String,
+    pub generator: Option<Box<GeneratorSpec>>,
+    pub seed: Option<u64>,
+}
+
+/// Specs of all generators, written to the output files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GeneratorSpec {
+    Equal(EqualSpec),
+    Random(RandomSpec),
+    Linear(LinearSpec),
+    Integer(IntegerSpec),
+    PhaseTransition(PhaseTransitionSpec),
+    PerfectPartition(PerfectPartitionSpec),
+    NoPerfectPartition(NoPerfectPartitionSpec),
+    TruncatedNormal(TruncatedNormalSpec),
+    Exponential(ExponentialSpec),
+    Pareto(ParetoSpec),
+    Clustered(ClusteredSpec),
+    OneGiant(OneGiantSpec),
+    /// Weights loaded from an instance file
+    Instance(InstanceSpec),
+    /// Generators defined outside of this crate, identified by name
+    Custom(String),
+}
+
+impl WeightGenerator for EqualSpec {
+    fn generate(&self, _: &mut StdRng) -> Vec<f64> {
+        equal_distribution(self.num_weights, self.value)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Equal(self.clone())
+    }
+}
+
+impl WeightGenerator for RandomSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        random_distribution(self.num_weights, self.min_value, self.max_value, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Random(self.clone())
+    }
+}
+
+impl WeightGenerator for LinearSpec {
+    fn generate(&self, _: &mut StdRng) -> Vec<f64> {
+        linear_distribution(self.num_weights, self.min_value, self.max_value)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Linear(self.clone())
+    }
+}
+
+impl WeightGenerator for IntegerSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        integer_distribution(self.num_weights, self.bits, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Integer(self.clone())
+    }
+}
+
+impl WeightGenerator for PhaseTransitionSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        phase_transition_distribution(self.num_weights, self.kappa, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::PhaseTransition(self.clone())
+    }
+}
+
+impl WeightGenerator for PerfectPartitionSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        perfect_partition_distribution(self.num_weights, self.bits, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::PerfectPartition(self.clone())
+    }
+}
+
+impl WeightGenerator for NoPerfectPartitionSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        no_perfect_partition_distribution(self.num_weights, self.bits, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::NoPerfectPartition(self.clone())
+    }
+}
+
+impl WeightGenerator for TruncatedNormalSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        truncated_normal_distribution(self.num_weights, self.mean, self.std_dev, self.min_value, self.max_value, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::TruncatedNormal(self.clone())
+    }
+}
+
+impl WeightGenerator for ExponentialSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        exponential_distribution(self.num_weights, self.rate, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Exponential(self.clone())
+    }
+}
+
+impl WeightGenerator for ParetoSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        pareto_distribution(self.num_weights, self.scale, self.shape, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Pareto(self.clone())
+    }
+}
+
+impl WeightGenerator for ClusteredSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        clustered_distribution(self.num_weights, &self.centers, self.std_dev, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Clustered(self.clone())
+    }
+}
+
+impl WeightGenerator for OneGiantSpec {
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        one_giant_distribution(self.num_weights, self.giant_value, self.min_value, self.max_value, rng)
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::OneGiant(self.clone())
+    }
+}
+
+impl WeightGenerator for GeneratorSpec {
+    /// Instances and custom generators cannot be recreated from their name and yield no weights
+    fn generate(&self, rng: &mut StdRng) -> Vec<f64> {
+        match self {
+            GeneratorSpec::Equal(spec) => spec.generate(rng),
+            GeneratorSpec::Random(spec) => spec.generate(rng),
+            GeneratorSpec::Linear(spec) => spec.generate(rng),
+            GeneratorSpec::Integer(spec) => spec.generate(rng),
+            GeneratorSpec::PhaseTransition(spec) => spec.generate(rng),
+            GeneratorSpec::PerfectPartition(spec) => spec.generate(rng),
+            GeneratorSpec::NoPerfectPartition(spec) => spec.generate(rng),
+            GeneratorSpec::TruncatedNormal(spec) => spec.generate(rng),
+            GeneratorSpec::Exponential(spec) => spec.generate(rng),
+            GeneratorSpec::Pareto(spec) => spec.generate(rng),
+            GeneratorSpec::Clustered(spec) => spec.generate(rng),
+            GeneratorSpec::OneGiant(spec) => spec.generate(rng),
+            GeneratorSpec::Instance(_) | GeneratorSpec::Custom(_) => Vec::new(),
+        }
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+    use super::{WeightGenerator, GeneratorSpec, RandomSpec, ClusteredSpec};
+
+    #[test]
+    fn test_spec_roundtrip() {
+        let generator = RandomSpec{ num_weights: 5, min_value: 1., max_value: 10. };
+        let json = serde_json::to_string(&generator.spec()).unwrap();
+        let spec: GeneratorSpec = serde_json::from_str(&json).unwrap();
+        assert_eq!(spec, GeneratorSpec::Random(generator.clone()));
+
+        // The spec and the seed reproduce the weights
+        let weights = generator.generate(&mut StdRng::seed_from_u64(4));
+        assert_eq!(spec.generate(&mut StdRng::seed_from_u64(4)), weights);
+        assert_eq!(weights.len(), 5);
+    }
+
+    #[test]
+    fn test_clustered_spec() {
+        let spec = GeneratorSpec::Clustered(ClusteredSpec{ num_weights: 3, centers: vec![5., 50.], std_dev: 1. });
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["Clustered"]["centers"], serde_json::json!([5., 50.]));
+    }
+}
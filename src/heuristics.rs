@@ -1,19 +1,40 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use crate::solution::CustomSolution;
+use crate::local_search::{local_search, LocalSearch};
+use crate::metaheuristics::{SimulatedAnnealing, TabuSearch};
 use serde::Serialize;
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum StartHeuristic {
     Greedy,
-    Random,
+    /// Uniformly random sides from the given seed
+    Random(u64),
+    /// First half of the items on one side, feasible for the balanced model
     EqualCount,
+    /// Greedy on pairs of items, sides differ by at most one item
+    BalancedGreedy,
+    KarmarkarKarp,
+    /// Constructs a solution and improves it by local search
+    Improved(Box<StartHeuristic>, LocalSearch),
+    SimulatedAnnealing(SimulatedAnnealing),
+    TabuSearch(TabuSearch),
 }
 
 pub fn solution_from_heuristic(object_vals: &Vec<f64>, start_heuristic: StartHeuristic) -> Option<CustomSolution> {
     let values = match start_heuristic {
         StartHeuristic::Greedy => greedy_heuristic(object_vals),
-        StartHeuristic::Random => random_heuristic(object_vals),
+        StartHeuristic::Random(seed) => random_heuristic(object_vals, seed),
         StartHeuristic::EqualCount => equal_heuristic(object_vals),
+        StartHeuristic::BalancedGreedy => balanced_greedy_heuristic(object_vals),
+        StartHeuristic::KarmarkarKarp => karmarkar_karp_heuristic(object_vals),
+        StartHeuristic::Improved(constructor, method) => {
+            let start = solution_from_heuristic(object_vals, *constructor)?;
+            local_search(object_vals, start.values(), method)
+        },
+        StartHeuristic::SimulatedAnnealing(annealing) => annealing.solve(object_vals),
+        StartHeuristic::TabuSearch(tabu) => tabu.solve(object_vals),
     };
 
     Some(CustomSolution::new(values))
@@ -37,8 +58,33 @@ fn greedy_heuristic(object_vals: &Vec<f64>) -> Vec<f64> {
     solution
 }
 
-fn random_heuristic(object_vals: &Vec<f64>) -> Vec<f64> {
-    let mut rng = rand::thread_rng();
+/// Puts the larger item of each pair onto the lighter side and the smaller one onto the other
+fn balanced_greedy_heuristic(object_vals: &[f64]) -> Vec<f64> {
+    let mut idx_sorted: Vec<_> = (0..object_vals.len()).collect();
+    idx_sorted.sort_by(|&i, &j| object_vals[j].total_cmp(&object_vals[i]));
+
+    let mut solution = vec![0.; object_vals.len()+1];
+    let (mut sum_left, mut sum_right) = (0., 0.);
+    for pair in idx_sorted.chunks(2) {
+        let (lighter, other) = if sum_left < sum_right { (0., 1.) } else { (1., 0.) };
+        solution[pair[0]] = lighter;
+        if let Some(&idx) = pair.get(1) {
+            solution[idx] = other;
+        }
+        for &idx in pair {
+            if solution[idx] == 1. {
+                sum_right += object_vals[idx];
+            } else {
+                sum_left += object_vals[idx];
+            }
+        }
+    }
+    solution[object_vals.len()] = (sum_left - sum_right).abs();
+    solution
+}
+
+fn random_heuristic(object_vals: &Vec<f64>, seed: u64) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
     let mut solution: Vec<_> = (0..object_vals.len()+1)
         .map(|_| rng.gen_bool(0.5) as i32 as f64)
         .collect();
@@ -61,10 +107,73 @@ fn equal_heuristic(object_vals: &Vec<f64>) -> Vec<f64> {
     solution
 }
 
+/// Node of the differencing tree, represented by one of the original items
+#[derive(PartialEq)]
+struct KKNode {
+    value: f64,
+    idx: usize,
+}
+
+impl Eq for KKNode {}
+
+impl PartialOrd for KKNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KKNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.total_cmp(&other.value)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+fn karmarkar_karp_heuristic(object_vals: &[f64]) -> Vec<f64> {
+    let mut heap: BinaryHeap<_> = object_vals.iter().enumerate()
+        .map(|(idx, &value)| KKNode{ value, idx })
+        .collect();
+
+    // Differencing the two largest values puts their items on opposite sides
+    let mut edges = vec![Vec::new(); object_vals.len()];
+    while heap.len() > 1 {
+        let large = heap.pop().unwrap();
+        let small = heap.pop().unwrap();
+        edges[large.idx].push(small.idx);
+        edges[small.idx].push(large.idx);
+        heap.push(KKNode{ value: large.value - small.value, idx: large.idx });
+    }
+
+    // The edges form a spanning tree, two-colouring it yields the assignment
+    let mut solution = vec![0.; object_vals.len()+1];
+    let mut visited = vec![false; object_vals.len()];
+    let mut stack: Vec<_> = heap.pop().map(|root| root.idx).into_iter().collect();
+    stack.iter().for_each(|&root| visited[root] = true);
+    while let Some(idx) = stack.pop() {
+        for &next in &edges[idx] {
+            if !visited[next] {
+                visited[next] = true;
+                solution[next] = 1. - solution[idx];
+                stack.push(next);
+            }
+        }
+    }
+
+    solution[object_vals.len()] = object_vals.iter().enumerate()
+        .map(|(i, v)| (2.*solution[i] - 1.) * v)
+        .sum::<f64>().abs();
+    solution
+}
+
 #[cfg(test)]
 mod tests { 
     use super::greedy_heuristic;
     use super::equal_heuristic;
+    use super::random_heuristic;
+    use super::karmarkar_karp_heuristic;
+    use super::balanced_greedy_heuristic;
+    use super::{solution_from_heuristic, StartHeuristic};
+    use crate::local_search::LocalSearch;
 
     #[test]
     fn test_greedy1() {
@@ -88,6 +197,13 @@ mod tests {
         assert!(sol[5] - 2. < 1e-6);
     }
 
+    #[test]
+    fn test_random_seeded() {
+        let vals: Vec<_> = (1..=20).map(|i| i as f64).collect();
+        assert_eq!(random_heuristic(&vals, 5), random_heuristic(&vals, 5));
+        assert_ne!(random_heuristic(&vals, 5), random_heuristic(&vals, 6));
+    }
+
     #[test]
     fn test_equal() {
         let vals = vec![1., 1., 2., 2.];
@@ -98,4 +214,57 @@ mod tests {
         assert!(sol[3] - 1. < 1e-6);
         assert!(sol[4] - 2. < 1e-6);
     }
+
+    #[test]
+    fn test_balanced_greedy() {
+        // {7, 6, 3} vs {8, 5, 4}
+        let vals = [8., 7., 6., 5., 4., 3.];
+        let sol = balanced_greedy_heuristic(&vals);
+        assert_eq!(sol[..6], [1., 0., 0., 1., 1., 0.]);
+        assert!((sol[6] - 1.).abs() < 1e-6);
+
+        // Greedy puts all small items onto one side
+        let vals = vec![10., 1., 1., 1.];
+        assert_eq!(greedy_heuristic(&vals)[..4], [1., 0., 0., 0.]);
+        let sol = balanced_greedy_heuristic(&vals);
+        assert_eq!(sol[..4].iter().sum::<f64>(), 2.);
+        assert!((sol[4] - 9.).abs() < 1e-6);
+
+        let sol = balanced_greedy_heuristic(&[5., 4., 3.]);
+        assert_eq!(sol[..3].iter().sum::<f64>(), 1.);
+        assert!((sol[3] - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_karmarkar_karp() {
+        // Greedy ends with a difference of 6, differencing with 4: {10, 8} vs {9, 7, 6}
+        let vals = vec![10., 9., 8., 7., 6.];
+        let sol = karmarkar_karp_heuristic(&vals);
+        assert!((sol[5] - 4.).abs() < 1e-6);
+        assert_eq!(sol[0], sol[2]);
+        assert_eq!(sol[1], sol[3]);
+        assert_eq!(sol[1], sol[4]);
+        assert_ne!(sol[0], sol[1]);
+    }
+
+    #[test]
+    fn test_improved() {
+        // Greedy ends with {8, 5, 4} vs {7, 6} and a difference of 4
+        let vals = vec![8., 7., 6., 5., 4.];
+        let heuristic = StartHeuristic::Improved(Box::new(StartHeuristic::Greedy), LocalSearch::Swaps);
+        let sol = solution_from_heuristic(&vals, heuristic).unwrap();
+        assert!(sol.values()[5] < 1e-6);
+    }
+
+    #[test]
+    fn test_karmarkar_karp_layout() {
+        let vals = vec![4., 5., 6., 7., 8.];
+        let sol = karmarkar_karp_heuristic(&vals);
+        assert_eq!(sol.len(), vals.len() + 1);
+        assert!(sol[..5].iter().all(|&s| s == 0. || s == 1.));
+
+        let sol = karmarkar_karp_heuristic(&[3.]);
+        assert!((sol[1] - 3.).abs() < 1e-6);
+        assert_eq!(karmarkar_karp_heuristic(&[]), vec![0.]);
+    }
 }
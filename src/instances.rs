@@ -0,0 +1,133 @@
+use std::{fs, io, path::Path};
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Serialize, Deserialize};
+use crate::generators::{WeightGenerator, GeneratorSpec, InstanceSpec};
+
+/// Named weight set stored as JSON or as plain text with one weight per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+    pub name: String,
+    pub weights: Vec<f64>,
+    /// How the weights were generated, not stored in plain text files
+    pub generator: Option<GeneratorSpec>,
+    pub seed: Option<u64>,
+}
+
+impl Instance {
+    pub fn new(name: &str, weights: Vec<f64>) -> Self {
+        Instance {
+            name: name.to_string(),
+            weights,
+            generator: None,
+            seed: None,
+        }
+    }
+
+    pub fn generate(name: &str, generator: &impl WeightGenerator, seed: u64) -> Self {
+        Instance {
+            name: name.to_string(),
+            weights: generator.generate(&mut StdRng::seed_from_u64(seed)),
+            generator: Some(generator.spec()),
+            seed: Some(seed),
+        }
+    }
+
+    /// Reads JSON for `.json` files and plain text otherwise, the name defaults to the file stem
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+
+        if is_json(path) {
+            return serde_json::from_str(&content).map_err(io::Error::from);
+        }
+
+        // Empty lines and comments starting with '#' are skipped
+        let weights = content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.parse::<f64>()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", line, err))))
+            .collect::<io::Result<_>>()?;
+        Ok(Instance::new(name, weights))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let content = if is_json(path) {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.weights.iter().map(|w| format!("{}\n", w)).collect()
+        };
+        fs::write(path, content)
+    }
+
+    /// Loads all instance files of the directory, ordered by file name
+    pub fn load_dir(dir: impl AsRef<Path>) -> io::Result<Vec<Self>> {
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<_>>()?;
+        paths.retain(|p| p.is_file());
+        paths.sort();
+        paths.iter().map(Instance::load).collect()
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Returns the stored weights regardless of the seed, the spec keeps the stored generator and seed
+impl WeightGenerator for Instance {
+    fn generate(&self, _: &mut StdRng) -> Vec<f64> {
+        self.weights.clone()
+    }
+
+    fn spec(&self) -> GeneratorSpec {
+        GeneratorSpec::Instance(InstanceSpec {
+            name: self.name.clone(),
+            generator: self.generator.clone().map(Box::new),
+            seed: self.seed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use rand::{SeedableRng, rngs::StdRng};
+    use crate::generators::{WeightGenerator, GeneratorSpec, IntegerSpec, InstanceSpec};
+    use super::Instance;
+
+    #[test]
+    fn test_save_load() {
+        let dir = std::env::temp_dir().join("lp_solver_test_instances");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let generator = IntegerSpec{ num_weights: 6, bits: 8 };
+        let instance = Instance::generate("int", &generator, 3);
+        instance.save(dir.join("b.json")).unwrap();
+        instance.save(dir.join("a.txt")).unwrap();
+        fs::write(dir.join("c.txt"), "# comment\n1.5\n\n2\n").unwrap();
+
+        let loaded = Instance::load_dir(&dir).unwrap();
+        assert_eq!(loaded[0], Instance::new("a", instance.weights.clone()));
+        assert_eq!(loaded[1], instance);
+        assert_eq!(loaded[2].weights, vec![1.5, 2.]);
+        assert_eq!(loaded[1].generator, Some(GeneratorSpec::Integer(generator.clone())));
+
+        assert_eq!(loaded[2].generate(&mut StdRng::seed_from_u64(0)), vec![1.5, 2.]);
+        assert_eq!(loaded[2].spec(), GeneratorSpec::Instance(InstanceSpec{ name: "c".to_string(), generator: None, seed: None }));
+        assert_eq!(loaded[1].spec(), GeneratorSpec::Instance(InstanceSpec{
+            name: "int".to_string(),
+            generator: Some(Box::new(GeneratorSpec::Integer(generator.clone()))),
+            seed: Some(3),
+        }));
+
+        fs::write(dir.join("d.txt"), "1\nx\n").unwrap();
+        assert!(Instance::load(dir.join("d.txt")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
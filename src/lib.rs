@@ -1,5 +1,21 @@
 pub mod custom_solver;
 pub mod comparison;
 pub mod weight_functions;
+pub mod generators;
+pub mod instances;
 pub mod heuristics;
 pub mod solution;
+pub mod solver;
+pub mod algorithm;
+pub mod exact;
+pub mod partitioning;
+pub mod multiway;
+pub mod models;
+pub mod benchmark;
+pub mod local_search;
+pub mod metaheuristics;
+pub mod mip_heuristics;
+pub mod validation;
+pub mod equivalence;
+pub mod lns;
+pub mod primal_heuristic;
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use good_lp::{constraint, variable::UnsolvedProblem, Constraint, Expression, Solution, Variable};
+use serde::Serialize;
+use crate::solution::CustomSolution;
+
+/// Values closer than this count as equal resp. integral
+const TOL: f64 = 1e-6;
+
+/// Sub-MIPs searched around the incumbent or the relaxation of a node
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum Neighbourhood {
+    /// Fixes the integer variables on which incumbent and relaxation agree
+    Rins,
+    /// Fixes the integral variables of the relaxation and restricts the others to the two nearest integers
+    Rens,
+    /// Allows at most `k` binary variables to differ from the incumbent
+    LocalBranching(usize),
+}
+
+/// Restricted problem and constraints of the neighbourhood, `None` if it does not apply
+pub(crate) fn restrict(
+    neighbourhood: Neighbourhood, problem: &UnsolvedProblem, constraints: &[Constraint],
+    int_vars: &HashSet<Variable>, relaxation: &CustomSolution, incumbent: Option<&CustomSolution>,
+) -> Option<(UnsolvedProblem, Vec<Constraint>)> {
+    let mut problem = problem.clone();
+    let mut constraints = constraints.to_vec();
+    let mut int_vars: Vec<_> = int_vars.iter().copied().collect();
+    int_vars.sort_by_key(|v| v.index());
+
+    match neighbourhood {
+        Neighbourhood::Rins => {
+            let incumbent = incumbent?;
+            let agreeing: Vec<_> = int_vars.iter()
+                .filter(|v| (incumbent.value(**v) - relaxation.value(**v)).abs() < TOL)
+                .collect();
+            if agreeing.is_empty() {
+                return None;
+            }
+            for var in agreeing {
+                let def = &mut problem.variables.variables[var.index()];
+                def.min = incumbent.value(*var).round();
+                def.max = def.min;
+            }
+        },
+        Neighbourhood::Rens => {
+            let is_fractional = |v: &Variable| (relaxation.value(*v) - relaxation.value(*v).round()).abs() > TOL;
+            if !int_vars.iter().any(is_fractional) {
+                return None;
+            }
+            for var in &int_vars {
+                let value = relaxation.value(*var);
+                let def = &mut problem.variables.variables[var.index()];
+                if is_fractional(var) {
+                    def.min = def.min.max(value.floor());
+                    def.max = def.max.min(value.ceil());
+                } else {
+                    def.min = value.round();
+                    def.max = def.min;
+                }
+            }
+        },
+        Neighbourhood::LocalBranching(k) => {
+            let incumbent = incumbent?;
+            let binaries: Vec<_> = int_vars.iter()
+                .filter(|v| {
+                    let def = &problem.variables.variables[v.index()];
+                    def.min == 0. && def.max == 1.
+                })
+                .collect();
+            if binaries.is_empty() {
+                return None;
+            }
+            let distance: Expression = binaries.iter()
+                .map(|v| if incumbent.value(**v) > 0.5 { 1 - **v } else { Expression::from(**v) })
+                .sum();
+            constraints.push(constraint!(distance <= k as f64));
+        },
+    }
+
+    Some((problem, constraints))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use good_lp::{constraint, variable, ProblemVariables, Variable, IntoAffineExpression};
+    use crate::solution::CustomSolution;
+    use super::{restrict, Neighbourhood};
+
+    #[test]
+    fn test_neighbourhoods() {
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = (0..4)
+            .map(|_| problem.add(variable().min(0).max(1)))
+            .collect();
+        let problem = problem.maximise(vars[0] + vars[1] + vars[2] + vars[3]);
+        let constraints = vec![constraint!(vars[0] + vars[1] + vars[2] + vars[3] <= 2.5)];
+        let int_vars: HashSet<_> = vars.iter().copied().collect();
+
+        let relaxation = CustomSolution::new(vec![1., 1., 0.5, 0.]);
+        let incumbent = CustomSolution::new(vec![1., 0., 1., 0.]);
+
+        let (rins, _) = restrict(Neighbourhood::Rins, &problem, &constraints, &int_vars, &relaxation, Some(&incumbent)).unwrap();
+        let bounds: Vec<_> = rins.variables.variables.iter().map(|d| (d.min, d.max)).collect();
+        assert_eq!(bounds, vec![(1., 1.), (0., 1.), (0., 1.), (0., 0.)]);
+        assert!(restrict(Neighbourhood::Rins, &problem, &constraints, &int_vars, &relaxation, None).is_none());
+
+        let (rens, _) = restrict(Neighbourhood::Rens, &problem, &constraints, &int_vars, &relaxation, None).unwrap();
+        let bounds: Vec<_> = rens.variables.variables.iter().map(|d| (d.min, d.max)).collect();
+        assert_eq!(bounds, vec![(1., 1.), (1., 1.), (0., 1.), (0., 0.)]);
+        assert!(restrict(Neighbourhood::Rens, &problem, &constraints, &int_vars, &incumbent, None).is_none());
+
+        let (_, local) = restrict(Neighbourhood::LocalBranching(1), &problem, &constraints, &int_vars, &relaxation, Some(&incumbent)).unwrap();
+        assert_eq!(local.len(), 2);
+        // Distance of the relaxation to the incumbent is 1.5
+        assert!((local[1].expression.clone().eval_with(&relaxation) - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Serialize;
+use crate::solution::CustomSolution;
+
+/// Improvements smaller than this are ignored to avoid cycling on round-off
+const TOL: f64 = 1e-9;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum LocalSearch {
+    /// Moves single items to the other side
+    Moves,
+    /// Exchanges two items on opposite sides
+    Swaps,
+    /// Sequences of swaps where intermediate steps may be worse
+    KernighanLin,
+}
+
+/// Improves a partition with the layout of the start heuristics (assignment + trailing diff)
+pub fn improve_solution(object_vals: &[f64], solution: &CustomSolution, method: LocalSearch) -> CustomSolution {
+    let values = local_search(object_vals, solution.values(), method);
+    let mut improved = CustomSolution::new(values);
+    improved.num_evals = solution.num_evals;
+    improved
+}
+
+pub fn local_search(object_vals: &[f64], values: &[f64], method: LocalSearch) -> Vec<f64> {
+    let mut sides: Vec<_> = values[..object_vals.len()].iter()
+        .map(|v| v.round() == 1.)
+        .collect();
+
+    match method {
+        LocalSearch::Moves => while improve_by_move(object_vals, &mut sides) {},
+        LocalSearch::Swaps => while improve_by_swap(object_vals, &mut sides) {},
+        LocalSearch::KernighanLin => while kernighan_lin_pass(object_vals, &mut sides) {},
+    }
+
+    let mut solution: Vec<_> = sides.iter().map(|&s| s as i32 as f64).collect();
+    solution.push(signed_diff(object_vals, &sides).abs());
+    solution
+}
+
+/// Sum of the items with side `true` minus the others
+fn signed_diff(object_vals: &[f64], sides: &[bool]) -> f64 {
+    object_vals.iter().zip(sides)
+        .map(|(w, &side)| if side { *w } else { -w })
+        .sum()
+}
+
+/// Change of the signed difference when the item switches sides
+fn delta(object_vals: &[f64], sides: &[bool], i: usize) -> f64 {
+    if sides[i] { -2. * object_vals[i] } else { 2. * object_vals[i] }
+}
+
+/// Applies the best single move, returns false if none improves
+fn improve_by_move(object_vals: &[f64], sides: &mut [bool]) -> bool {
+    let diff = signed_diff(object_vals, sides);
+    let best = (0..sides.len())
+        .map(|i| (i, (diff + delta(object_vals, sides, i)).abs()))
+        .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+
+    match best {
+        Some((i, d)) if d < diff.abs() - TOL => {
+            sides[i] = !sides[i];
+            true
+        },
+        _ => false,
+    }
+}
+
+/// Best swap among the items not yet locked, with the resulting absolute difference
+fn best_swap(object_vals: &[f64], sides: &[bool], locked: &[bool]) -> Option<(usize, usize, f64)> {
+    let diff = signed_diff(object_vals, sides);
+    let candidates: Vec<_> = (0..sides.len()).filter(|&i| !locked[i]).collect();
+
+    candidates.iter()
+        .flat_map(|&i| candidates.iter().map(move |&j| (i, j)))
+        .filter(|&(i, j)| i < j && sides[i] != sides[j])
+        .map(|(i, j)| (i, j, (diff + delta(object_vals, sides, i) + delta(object_vals, sides, j)).abs()))
+        .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+}
+
+/// Applies the best swap, returns false if none improves
+fn improve_by_swap(object_vals: &[f64], sides: &mut [bool]) -> bool {
+    let diff = signed_diff(object_vals, sides).abs();
+    match best_swap(object_vals, sides, &vec![false; sides.len()]) {
+        Some((i, j, d)) if d < diff - TOL => {
+            sides[i] = !sides[i];
+            sides[j] = !sides[j];
+            true
+        },
+        _ => false,
+    }
+}
+
+/// Performs swaps until all items are locked and keeps the best prefix,
+/// returns false if no prefix improves
+fn kernighan_lin_pass(object_vals: &[f64], sides: &mut [bool]) -> bool {
+    let start_diff = signed_diff(object_vals, sides).abs();
+    let mut current = sides.to_vec();
+    let mut locked = vec![false; sides.len()];
+    let mut best = (start_diff, 0);
+    let mut swaps = Vec::new();
+
+    while let Some((i, j, d)) = best_swap(object_vals, &current, &locked) {
+        current[i] = !current[i];
+        current[j] = !current[j];
+        locked[i] = true;
+        locked[j] = true;
+        swaps.push((i, j));
+        if d < best.0 - TOL {
+            best = (d, swaps.len());
+        }
+    }
+
+    for &(i, j) in &swaps[..best.1] {
+        sides[i] = !sides[i];
+        sides[j] = !sides[j];
+    }
+    best.1 > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{local_search, LocalSearch};
+
+    #[test]
+    fn test_moves() {
+        let vals = [4., 3., 2., 1.];
+        let sol = local_search(&vals, &[1., 1., 1., 1., 10.], LocalSearch::Moves);
+        assert!(sol[4] < 1e-6);
+    }
+
+    #[test]
+    fn test_swaps() {
+        // Equal counts on both sides stay equal
+        let vals = [5., 4., 3., 2.];
+        let sol = local_search(&vals, &[1., 1., 0., 0., 4.], LocalSearch::Swaps);
+        assert!(sol[4] < 1e-6);
+        assert_eq!(sol[..4].iter().sum::<f64>(), 2.);
+    }
+
+    #[test]
+    fn test_kernighan_lin() {
+        // Swaps get stuck at a difference of 3, accepting a worse swap within a pass reaches 1
+        let vals = [8., 24., 26., 13., 14., 22., 6., 12.];
+        let start = [1., 1., 1., 1., 0., 0., 0., 0., 17.];
+        let sol = local_search(&vals, &start, LocalSearch::Swaps);
+        assert!((sol[8] - 3.).abs() < 1e-6);
+
+        let sol = local_search(&vals, &start, LocalSearch::KernighanLin);
+        assert!((sol[8] - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_no_worsening() {
+        let vals = [3., 3., 3.];
+        for method in [LocalSearch::Moves, LocalSearch::Swaps, LocalSearch::KernighanLin] {
+            let sol = local_search(&vals, &[1., 0., 0., 3.], method);
+            assert!((sol[3] - 3.).abs() < 1e-6);
+        }
+    }
+}
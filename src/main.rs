@@ -1,34 +1,65 @@
-use serde_json;
-use lp_solver::{comparison::compare_solvers, 
-    weight_functions::{random_distribution, equal_distribution, linear_distribution},
-    heuristics::StartHeuristic,
+use std::path::Path;
+use lp_solver::{comparison::compare_solvers,
+    generators::RandomSpec,
+    instances::Instance,
+    equivalence::Verification,
+    benchmark::RunStatus,
 };
 
+/// Writes the instance library, only run with `--create-library` as the files are versioned.
+/// The generator must use the RNG, deterministic specs would repeat the same weights for every seed.
+fn create_library(dir: &Path, num_repeats: usize, min_weight: f64, max_weight: f64) {
+    std::fs::create_dir_all(dir).unwrap();
+    for num_vars in (5..=19).step_by(2) {
+        for i in 0..num_repeats {
+            let seed = (num_vars * num_repeats + i) as u64;
+            let generator = RandomSpec{ num_weights: num_vars, min_value: min_weight, max_value: max_weight };
+            let name = format!("random_{:02}_{:02}", num_vars, i);
+            Instance::generate(&name, &generator, seed)
+                .save(dir.join(format!("{}.json", name)))
+                .unwrap();
+        }
+    }
+}
+
 fn main() {
     let num_repeats = 10;
     let min_weight = 1.;
     let max_weight = 100.;
 
-    let mut solutions = Vec::new();
-    for num_vars in (5..=19).step_by(2) {
-        for i in 0..num_repeats {
-            print!("Vars: {}, Iter: {}/{} ... ", num_vars, i, num_repeats);
-            //let weights_fn = || random_distribution(num_vars, min_weight, max_weight);
-            let weights_fn = || linear_distribution(num_vars, min_weight, max_weight);
-            //let weights_fn = || equal_distribution(num_vars, max_weight);
-            let sols = compare_solvers(weights_fn, None);
+    let library = Path::new("data/instances");
+    if std::env::args().any(|arg| arg == "--create-library") {
+        create_library(library, num_repeats, min_weight, max_weight);
+        return;
+    }
+    let instances = Instance::load_dir(library).unwrap();
 
-            let duration = sols[1].duration_sec;
-            println!("{:?}", duration);
+    let mut solutions = Vec::new();
+    for (i, instance) in instances.iter().enumerate() {
+        print!("Instance: {} ({}/{}) ... ", instance.name, i, instances.len());
+        let sols = compare_solvers(instance, instance.seed.unwrap_or_default(), None, &[]);
 
-            solutions.push(sols);
+        let duration = sols[1].duration_sec;
+        println!("{:?}", duration);
+        for sol in &sols {
+            if let RunStatus::Failed(err) = &sol.status {
+                println!("  {:?} failed: {}", sol.used_solver, err);
+            }
+            if let Some(violations) = sol.violations.as_ref().filter(|v| !v.is_feasible()) {
+                println!("  {:?} violates the model: {:?}", sol.used_solver, violations);
+            }
+            if let Some(Verification::Discrepant(discrepancies)) = &sol.verification {
+                println!("  {:?} is discrepant: {:?}", sol.used_solver, discrepancies);
+            }
         }
+
+        solutions.push(sols);
     }
 
     let solutions: Vec<_> = solutions.iter().flatten().collect();
 
     std::fs::write(
-        "data/data_linear_none.json",
+        "data/data_random_none.json",
         serde_json::to_string_pretty(&solutions).unwrap()
     ).unwrap();
 }
@@ -0,0 +1,275 @@
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::Serialize;
+
+/// Differences below this count as a perfect partition and end the search
+const TOL: f64 = 1e-9;
+
+/// How the temperature decreases over the iterations
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub enum Cooling {
+    /// Multiplies the temperature by the factor in each iteration
+    Geometric(f64),
+    /// Decreases linearly from the start to the end temperature
+    Linear,
+    /// Start temperature divided by `ln(e + k)` in iteration `k`
+    Logarithmic,
+}
+
+/// Simulated annealing on single item moves.
+/// Temperatures are relative to the largest weight.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SimulatedAnnealing {
+    start_temperature: f64,
+    end_temperature: f64,
+    cooling: Cooling,
+    max_iterations: usize,
+    time_limit: Option<Duration>,
+    seed: u64,
+}
+
+impl SimulatedAnnealing {
+    pub fn new(seed: u64) -> Self {
+        SimulatedAnnealing {
+            start_temperature: 0.5,
+            end_temperature: 1e-4,
+            cooling: Cooling::Geometric(0.999),
+            max_iterations: 10_000,
+            time_limit: None,
+            seed,
+        }
+    }
+
+    pub fn with_temperatures(mut self, start: f64, end: f64) -> Self {
+        self.start_temperature = start;
+        self.end_temperature = end;
+        self
+    }
+
+    pub fn with_cooling(mut self, cooling: Cooling) -> Self {
+        self.cooling = cooling;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    fn temperature(&self, iteration: usize) -> f64 {
+        let t = match self.cooling {
+            Cooling::Geometric(factor) => self.start_temperature * factor.powi(iteration as i32),
+            Cooling::Linear => self.start_temperature - (self.start_temperature - self.end_temperature)
+                * iteration as f64 / self.max_iterations.max(1) as f64,
+            Cooling::Logarithmic => self.start_temperature / (std::f64::consts::E + iteration as f64).ln(),
+        };
+        t.max(self.end_temperature)
+    }
+
+    /// Returns the best partition found, with the layout of the start heuristics
+    pub fn solve(&self, object_vals: &[f64]) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut search = Search::new(object_vals, &mut rng, self.time_limit);
+        let scale = object_vals.iter().cloned().fold(0., f64::max);
+
+        for iteration in 0..self.max_iterations {
+            if search.finished() {
+                break;
+            }
+
+            let i = rng.gen_range(0..object_vals.len());
+            let change = search.move_diff(i).abs() - search.diff.abs();
+            let temperature = self.temperature(iteration) * scale;
+            if change <= 0. || rng.gen_bool((-change / temperature).exp().min(1.)) {
+                search.apply(i);
+            }
+        }
+
+        search.best_values()
+    }
+}
+
+/// Tabu search on single item moves, items may not move back for `tenure` iterations
+/// unless that yields a new best partition (aspiration)
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct TabuSearch {
+    tenure: usize,
+    max_iterations: usize,
+    time_limit: Option<Duration>,
+    seed: u64,
+}
+
+impl TabuSearch {
+    pub fn new(seed: u64) -> Self {
+        TabuSearch {
+            tenure: 7,
+            max_iterations: 1_000,
+            time_limit: None,
+            seed,
+        }
+    }
+
+    pub fn with_tenure(mut self, tenure: usize) -> Self {
+        self.tenure = tenure;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Returns the best partition found, with the layout of the start heuristics
+    pub fn solve(&self, object_vals: &[f64]) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut search = Search::new(object_vals, &mut rng, self.time_limit);
+        // Iteration from which on the item may move again
+        let mut tabu_until = vec![0; object_vals.len()];
+
+        for iteration in 0..self.max_iterations {
+            if search.finished() {
+                break;
+            }
+
+            // Best admissible move, ties broken randomly
+            let offset = rng.gen_range(0..object_vals.len());
+            let best = (0..object_vals.len())
+                .map(|k| (k + offset) % object_vals.len())
+                .map(|i| (i, search.move_diff(i).abs()))
+                .filter(|&(i, diff)| tabu_until[i] <= iteration || diff < search.best_diff - TOL)
+                .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+
+            let Some((i, _)) = best else {
+                continue;
+            };
+            search.apply(i);
+            tabu_until[i] = iteration + self.tenure + 1;
+        }
+
+        search.best_values()
+    }
+}
+
+/// Current and best partition with their signed differences
+struct Search<'a> {
+    object_vals: &'a [f64],
+    sides: Vec<bool>,
+    diff: f64,
+    best_sides: Vec<bool>,
+    best_diff: f64,
+    deadline: Option<Instant>,
+}
+
+impl<'a> Search<'a> {
+    /// Starts from a random partition
+    fn new(object_vals: &'a [f64], rng: &mut impl Rng, time_limit: Option<Duration>) -> Self {
+        let sides: Vec<_> = object_vals.iter().map(|_| rng.gen_bool(0.5)).collect();
+        let diff = object_vals.iter().zip(&sides)
+            .map(|(w, &side)| if side { *w } else { -w })
+            .sum::<f64>();
+
+        Search {
+            object_vals,
+            best_sides: sides.clone(),
+            best_diff: diff.abs(),
+            sides,
+            diff,
+            deadline: time_limit.map(|limit| Instant::now() + limit),
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.best_diff <= TOL
+            || self.object_vals.is_empty()
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Signed difference after moving the item to the other side
+    fn move_diff(&self, i: usize) -> f64 {
+        if self.sides[i] {
+            self.diff - 2. * self.object_vals[i]
+        } else {
+            self.diff + 2. * self.object_vals[i]
+        }
+    }
+
+    fn apply(&mut self, i: usize) {
+        self.diff = self.move_diff(i);
+        self.sides[i] = !self.sides[i];
+        if self.diff.abs() < self.best_diff {
+            self.best_diff = self.diff.abs();
+            self.best_sides.clone_from(&self.sides);
+        }
+    }
+
+    fn best_values(&self) -> Vec<f64> {
+        let mut values: Vec<_> = self.best_sides.iter().map(|&s| s as i32 as f64).collect();
+        values.push(self.object_vals.iter().zip(&self.best_sides)
+            .map(|(w, &side)| if side { *w } else { -w })
+            .sum::<f64>().abs());
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::{SimulatedAnnealing, TabuSearch, Cooling};
+
+    fn diff(weights: &[f64], values: &[f64]) -> f64 {
+        weights.iter().zip(values)
+            .map(|(w, v)| (2. * v - 1.) * w)
+            .sum::<f64>().abs()
+    }
+
+    #[test]
+    fn test_annealing() {
+        let weights = [8., 7., 6., 5., 4.];
+        for cooling in [Cooling::Geometric(0.99), Cooling::Linear, Cooling::Logarithmic] {
+            let sol = SimulatedAnnealing::new(42).with_cooling(cooling).solve(&weights);
+            assert!(sol[5] < 1e-6);
+            assert!(diff(&weights, &sol) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_tabu() {
+        let weights = [8., 24., 26., 13., 14., 22., 6., 12.];
+        let sol = TabuSearch::new(42).solve(&weights);
+        assert!((sol[8] - 1.).abs() < 1e-6);
+        assert!((diff(&weights, &sol) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let weights: Vec<_> = (1..=40).map(|i| (i as f64).sqrt()).collect();
+
+        let annealing = SimulatedAnnealing::new(7).with_max_iterations(500);
+        assert_eq!(annealing.solve(&weights), annealing.solve(&weights));
+
+        let tabu = TabuSearch::new(7).with_max_iterations(50);
+        assert_eq!(tabu.solve(&weights), tabu.solve(&weights));
+    }
+
+    #[test]
+    fn test_budget() {
+        let weights: Vec<_> = (1..=40).map(|i| (i as f64).sqrt()).collect();
+
+        let sol = SimulatedAnnealing::new(0).with_time_limit(Duration::ZERO).solve(&weights);
+        assert_eq!(sol.len(), weights.len() + 1);
+        assert!((diff(&weights, &sol) - sol[40]).abs() < 1e-9);
+
+        let sol = TabuSearch::new(0).with_max_iterations(0).solve(&weights);
+        assert!((diff(&weights, &sol) - sol[40]).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+use good_lp::{constraint, variable, variable::UnsolvedProblem, Constraint, Expression,
+    IntoAffineExpression, Solution, Variable, solvers::ObjectiveDirection};
+use serde::Serialize;
+use crate::custom_solver::LpSolveFn;
+use crate::solution::CustomSolution;
+
+/// Values closer than this to an integer count as integral
+const INT_TOL: f64 = 1e-6;
+const MAX_PUMP_ITERATIONS: usize = 50;
+
+/// Primal heuristics that only rely on the model, not on its structure
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum MipHeuristic {
+    /// Rounds the relaxation and re-solves for the continuous variables
+    Rounding,
+    /// Dives by rounding the least fractional variable
+    FractionalDiving,
+    /// Dives by rounding in the direction with the fewest locking constraints
+    CoefficientDiving,
+    /// Dives towards the incumbent, needs one to exist
+    GuidedDiving,
+    FeasibilityPump,
+}
+
+/// Relaxation of a node of the branch-and-bound tree
+pub(crate) struct MipContext<'a> {
+    pub problem: &'a UnsolvedProblem,
+    pub constraints: &'a [Constraint],
+    pub int_vars: &'a HashSet<Variable>,
+    pub solve_lp: &'a LpSolveFn,
+    pub incumbent: Option<&'a CustomSolution>,
+}
+
+/// Bounds of the integer variables tightened by a heuristic
+type Bounds = HashMap<Variable, (f64, f64)>;
+
+impl MipContext<'_> {
+    /// Returns a solution feasible for the model, starting from the node relaxation
+    pub fn run(&self, heuristic: MipHeuristic, relaxation: &CustomSolution) -> Option<CustomSolution> {
+        match heuristic {
+            MipHeuristic::Rounding => self.rounding(relaxation),
+            MipHeuristic::FractionalDiving
+            | MipHeuristic::CoefficientDiving
+            | MipHeuristic::GuidedDiving => self.dive(heuristic, relaxation),
+            MipHeuristic::FeasibilityPump => self.feasibility_pump(relaxation),
+        }
+    }
+
+    fn fractional_vars(&self, solution: &CustomSolution) -> Vec<Variable> {
+        let mut vars: Vec<_> = self.int_vars.iter()
+            .copied()
+            .filter(|v| (solution.value(*v) - solution.value(*v).round()).abs() > INT_TOL)
+            .collect();
+        vars.sort_by_key(|v| v.index());
+        vars
+    }
+
+    /// Solves the relaxation with the original objective and the given bounds
+    fn solve_with_bounds(&self, bounds: &Bounds) -> Option<CustomSolution> {
+        let mut problem = self.problem.clone();
+        for (var, (min, max)) in bounds {
+            let def = &mut problem.variables.variables[var.index()];
+            def.min = def.min.max(*min);
+            def.max = def.max.min(*max);
+            if def.min > def.max {
+                return None;
+            }
+        }
+        (self.solve_lp)(problem, self.constraints).ok().map(|(solution, _)| solution)
+    }
+
+    /// Fixes all integer variables to their rounded values
+    fn fix_rounded(&self, solution: &CustomSolution) -> Option<CustomSolution> {
+        let bounds = self.int_vars.iter()
+            .map(|v| (*v, (solution.value(*v).round(), solution.value(*v).round())))
+            .collect();
+        self.solve_with_bounds(&bounds)
+    }
+
+    fn rounding(&self, relaxation: &CustomSolution) -> Option<CustomSolution> {
+        self.fix_rounded(relaxation)
+    }
+
+    /// Number of constraints that may become violated when increasing resp. decreasing the variable
+    fn locks(&self) -> HashMap<Variable, (usize, usize)> {
+        let mut locks = HashMap::new();
+        for c in self.constraints {
+            for (var, coef) in c.expression.clone().linear_coefficients() {
+                let (up, down) = locks.entry(var).or_insert((0, 0));
+                if coef > 0. || c.is_equality { *up += 1; }
+                if coef < 0. || c.is_equality { *down += 1; }
+            }
+        }
+        locks
+    }
+
+    fn dive(&self, rule: MipHeuristic, relaxation: &CustomSolution) -> Option<CustomSolution> {
+        let incumbent = match rule {
+            MipHeuristic::GuidedDiving => Some(self.incumbent?),
+            _ => None,
+        };
+        let locks = self.locks();
+        let mut bounds = Bounds::new();
+        let mut solution = relaxation.clone();
+
+        // Every step removes the current value from the domain of one variable
+        for _ in 0..=2 * self.int_vars.len() {
+            let fractional = self.fractional_vars(&solution);
+            if fractional.is_empty() {
+                return Some(solution);
+            }
+
+            // Score to minimise and whether to round up
+            let (var, up) = fractional.into_iter()
+                .map(|v| {
+                    let x = solution.value(v);
+                    let frac = x - x.floor();
+                    let (score, up) = match rule {
+                        MipHeuristic::CoefficientDiving => {
+                            let (up_locks, down_locks) = locks.get(&v).copied().unwrap_or((0, 0));
+                            let up = up_locks < down_locks || (up_locks == down_locks && frac >= 0.5);
+                            (up_locks.min(down_locks) as f64 + frac.min(1. - frac), up)
+                        },
+                        MipHeuristic::GuidedDiving => {
+                            let target = incumbent.unwrap().value(v);
+                            ((x - target).abs(), target > x)
+                        },
+                        _ => (frac.min(1. - frac), frac >= 0.5),
+                    };
+                    (v, score, up)
+                })
+                .min_by(|(_, s1, _), (_, s2, _)| s1.total_cmp(s2))
+                .map(|(v, _, up)| (v, up))?;
+
+            let x = solution.value(var);
+            let bound = if up { (x.ceil(), f64::INFINITY) } else { (f64::NEG_INFINITY, x.floor()) };
+            let (min, max) = bounds.entry(var).or_insert((f64::NEG_INFINITY, f64::INFINITY));
+            *min = min.max(bound.0);
+            *max = max.min(bound.1);
+            solution = self.solve_with_bounds(&bounds)?;
+        }
+        None
+    }
+
+    /// Alternates between rounding and projecting the rounded point onto the relaxation
+    fn feasibility_pump(&self, relaxation: &CustomSolution) -> Option<CustomSolution> {
+        let mut int_vars: Vec<_> = self.int_vars.iter().copied().collect();
+        int_vars.sort_by_key(|v| v.index());
+
+        let mut solution = relaxation.clone();
+        let mut rounded: Vec<_> = int_vars.iter().map(|v| solution.value(*v).round()).collect();
+
+        for _ in 0..MAX_PUMP_ITERATIONS {
+            if self.fractional_vars(&solution).is_empty() {
+                return self.fix_rounded(&solution);
+            }
+
+            // Minimise the L1 distance to the rounded point
+            let mut problem = self.problem.clone();
+            let mut constraints = self.constraints.to_vec();
+            let mut distance = Expression::from(0.);
+            for (v, r) in int_vars.iter().zip(&rounded) {
+                let t = problem.variables.add(variable().min(0));
+                constraints.push(constraint!(t >= *v - *r));
+                constraints.push(constraint!(t >= *r - *v));
+                distance += t;
+            }
+            problem.objective = distance;
+            problem.direction = ObjectiveDirection::Minimisation;
+            solution = (self.solve_lp)(problem, &constraints).ok()?.0;
+
+            // Flip the most distant variable on cycling
+            let new_rounded: Vec<_> = int_vars.iter().map(|v| solution.value(*v).round()).collect();
+            if new_rounded == rounded {
+                let (k, x) = int_vars.iter().enumerate()
+                    .map(|(k, v)| (k, solution.value(*v)))
+                    .max_by(|(k1, x1), (k2, x2)| (x1 - rounded[*k1]).abs().total_cmp(&(x2 - rounded[*k2]).abs()))?;
+                rounded[k] += if x > rounded[k] { 1. } else { -1. };
+            } else {
+                rounded = new_rounded;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use good_lp::{constraint, variable, ProblemVariables, Solution, Variable, Expression,
+        solvers::highs::highs, IntoAffineExpression};
+    use crate::custom_solver::lp_solve_fn;
+    use super::{MipContext, MipHeuristic};
+
+    const HEURISTICS: [MipHeuristic; 4] = [
+        MipHeuristic::Rounding,
+        MipHeuristic::FractionalDiving,
+        MipHeuristic::CoefficientDiving,
+        MipHeuristic::FeasibilityPump,
+    ];
+
+    #[test]
+    fn test_feasible() {
+        // Knapsack with a side constraint, the relaxation is fractional
+        let weights = [9., 7., 5., 8., 5., 3., 7., 10.];
+        let mut problem = ProblemVariables::new();
+        let vars: Vec<Variable> = weights.iter()
+            .map(|_| problem.add(variable().min(0).max(1)))
+            .collect();
+        let obj: Expression = vars.iter().sum();
+        let lhs: Expression = weights.iter().zip(&vars).map(|(w, v)| *w * *v).sum();
+        let problem = problem.maximise(obj);
+        let constraints = vec![constraint!(lhs.clone() <= 20.5), constraint!(vars[0] + vars[7] >= 1)];
+        let int_vars: HashSet<_> = vars.iter().copied().collect();
+
+        let solve_lp = lp_solve_fn(highs);
+        let (relaxation, _) = solve_lp(problem.clone(), &constraints).unwrap();
+        let context = MipContext {
+            problem: &problem,
+            constraints: &constraints,
+            int_vars: &int_vars,
+            solve_lp: &solve_lp,
+            incumbent: None,
+        };
+
+        for heuristic in HEURISTICS {
+            let Some(solution) = context.run(heuristic, &relaxation) else {
+                // Plain rounding may violate the capacity
+                assert_eq!(heuristic, MipHeuristic::Rounding);
+                continue;
+            };
+            assert!(vars.iter().all(|v| (solution.value(*v) - solution.value(*v).round()).abs() < 1e-6));
+            assert!(lhs.clone().eval_with(&solution) <= 20.5 + 1e-6);
+            assert!(solution.value(vars[0]) + solution.value(vars[7]) >= 1. - 1e-6);
+        }
+
+        assert!(context.run(MipHeuristic::GuidedDiving, &relaxation).is_none());
+    }
+
+    #[test]
+    fn test_guided() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().min(0).max(4));
+        let y = problem.add(variable().min(0).max(4));
+        let problem = problem.maximise(x + y);
+        let constraints = vec![constraint!(2*x + 2*y <= 7)];
+        let int_vars: HashSet<_> = [x, y].into_iter().collect();
+
+        let solve_lp = lp_solve_fn(highs);
+        let (relaxation, _) = solve_lp(problem.clone(), &constraints).unwrap();
+        let incumbent = crate::solution::CustomSolution::new(vec![0., 3.]);
+        let context = MipContext {
+            problem: &problem,
+            constraints: &constraints,
+            int_vars: &int_vars,
+            solve_lp: &solve_lp,
+            incumbent: Some(&incumbent),
+        };
+
+        let solution = context.run(MipHeuristic::GuidedDiving, &relaxation).unwrap();
+        assert!((solution.value(x) + solution.value(y) - 3.).abs() < 1e-6);
+        assert!((solution.value(x) - solution.value(x).round()).abs() < 1e-6);
+    }
+}
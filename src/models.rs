@@ -0,0 +1,403 @@
+use good_lp::{constraint, variable, variable::UnsolvedProblem, Constraint, Expression, ProblemVariables, Variable};
+use rand::{Rng, rngs::StdRng};
+use serde::{Serialize, Deserialize};
+
+/// MIP with its constraints, the variables are listed in the order they were created
+#[derive(Clone)]
+pub struct MipModel {
+    pub problem: UnsolvedProblem,
+    pub constraints: Vec<Constraint>,
+    pub variables: Vec<Variable>,
+}
+
+/// Source of random MIP instances, described by a serialisable spec
+pub trait ModelBuilder {
+    fn build(&self, rng: &mut StdRng) -> MipModel;
+    fn spec(&self) -> ModelSpec;
+}
+
+/// 0/1 knapsack with uncorrelated integer weights and profits in `1..=max_value`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KnapsackSpec {
+    pub num_items: usize,
+    pub max_value: u32,
+    /// Capacity as share of the total weight
+    pub capacity_ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiKnapsackSpec {
+    pub num_items: usize,
+    pub num_dimensions: usize,
+    pub max_value: u32,
+    /// Capacity of each dimension as share of its total weight
+    pub capacity_ratio: f64,
+}
+
+/// Bin packing with integer item sizes, one bin per item is available
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinPackingSpec {
+    pub num_items: usize,
+    pub capacity: u32,
+    pub min_size: u32,
+    pub max_size: u32,
+}
+
+/// Weighted set cover, each set contains each element with probability `density`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetCoverSpec {
+    pub num_elements: usize,
+    pub num_sets: usize,
+    pub density: f64,
+    pub max_cost: u32,
+}
+
+/// Generalised assignment with costs in `10..=50` and resource needs in `5..=25`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssignmentSpec {
+    pub num_agents: usize,
+    pub num_jobs: usize,
+    /// Capacity of each agent as share of its total resource need divided by the number of agents
+    pub capacity_ratio: f64,
+}
+
+/// Uncapacitated facility location on random points of the unit square
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FacilityLocationSpec {
+    pub num_facilities: usize,
+    pub num_customers: usize,
+    pub opening_cost: f64,
+}
+
+/// Specs of all model builders, written to the output files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ModelSpec {
+    Knapsack(KnapsackSpec),
+    MultiKnapsack(MultiKnapsackSpec),
+    BinPacking(BinPackingSpec),
+    SetCover(SetCoverSpec),
+    Assignment(AssignmentSpec),
+    FacilityLocation(FacilityLocationSpec),
+}
+
+fn random_values(n: usize, min: u32, max: u32, rng: &mut StdRng) -> Vec<f64> {
+    assert!(min <= max, "empty range {}..={}", min, max);
+    (0..n).map(|_| rng.gen_range(min..=max) as f64).collect()
+}
+
+fn binaries(problem: &mut ProblemVariables, n: usize) -> Vec<Variable> {
+    (0..n).map(|_| problem.add(variable().binary())).collect()
+}
+
+fn weighted_sum(weights: &[f64], vars: &[Variable]) -> Expression {
+    weights.iter().zip(vars).map(|(w, v)| *w * *v).sum()
+}
+
+impl ModelBuilder for KnapsackSpec {
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        MultiKnapsackSpec {
+            num_items: self.num_items,
+            num_dimensions: 1,
+            max_value: self.max_value,
+            capacity_ratio: self.capacity_ratio,
+        }.build(rng)
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::Knapsack(self.clone())
+    }
+}
+
+impl ModelBuilder for MultiKnapsackSpec {
+    /// Panics for a zero `max_value` or a negative capacity ratio
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        assert!(self.max_value >= 1, "values are drawn from 1..={}", self.max_value);
+        assert!(self.capacity_ratio >= 0. && self.capacity_ratio.is_finite(),
+            "capacity ratio {} is not a non-negative number", self.capacity_ratio);
+        let profits = random_values(self.num_items, 1, self.max_value, rng);
+
+        let mut problem = ProblemVariables::new();
+        let vars = binaries(&mut problem, self.num_items);
+        let constraints = (0..self.num_dimensions)
+            .map(|_| {
+                let weights = random_values(self.num_items, 1, self.max_value, rng);
+                let capacity = (self.capacity_ratio * weights.iter().sum::<f64>()).floor();
+                constraint!(weighted_sum(&weights, &vars) <= capacity)
+            })
+            .collect();
+
+        MipModel {
+            problem: problem.maximise(weighted_sum(&profits, &vars)),
+            constraints,
+            variables: vars,
+        }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::MultiKnapsack(self.clone())
+    }
+}
+
+impl ModelBuilder for BinPackingSpec {
+    /// Bin `b` is only used if bin `b-1` is, the variables are the bins followed by the items bin by bin.
+    /// Panics for an empty size range or items larger than the capacity.
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        assert!(self.min_size <= self.max_size, "empty size range {}..={}", self.min_size, self.max_size);
+        assert!(self.max_size <= self.capacity, "items of size {} do not fit into bins of {}", self.max_size, self.capacity);
+        let sizes = random_values(self.num_items, self.min_size, self.max_size, rng);
+        let capacity = self.capacity as f64;
+
+        let mut problem = ProblemVariables::new();
+        let bins = binaries(&mut problem, self.num_items);
+        let assignment: Vec<_> = (0..self.num_items)
+            .map(|_| binaries(&mut problem, self.num_items))
+            .collect();
+
+        let mut constraints: Vec<_> = (0..self.num_items)
+            .map(|i| constraint!(assignment.iter().map(|row| row[i]).sum::<Expression>() == 1))
+            .collect();
+        for (bin, row) in bins.iter().zip(&assignment) {
+            constraints.push(constraint!(weighted_sum(&sizes, row) <= capacity * *bin));
+        }
+        for pair in bins.windows(2) {
+            constraints.push(constraint!(pair[1] <= pair[0]));
+        }
+
+        let objective: Expression = bins.iter().sum();
+        let variables = bins.into_iter().chain(assignment.into_iter().flatten()).collect();
+        MipModel { problem: problem.minimise(objective), constraints, variables }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::BinPacking(self.clone())
+    }
+}
+
+impl ModelBuilder for SetCoverSpec {
+    /// Elements not drawn into any set are added to a random one, so the instance is always feasible.
+    /// Panics without sets, for a density outside of `[0, 1]` or a zero `max_cost`.
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        assert!(self.num_sets >= 1, "set cover needs at least one set");
+        assert!((0. ..=1.).contains(&self.density), "density {} is not a probability", self.density);
+        assert!(self.max_cost >= 1, "costs are drawn from 1..={}", self.max_cost);
+        let costs = random_values(self.num_sets, 1, self.max_cost, rng);
+        let mut members: Vec<Vec<usize>> = (0..self.num_elements)
+            .map(|_| (0..self.num_sets).filter(|_| rng.gen_bool(self.density)).collect())
+            .collect();
+        for sets in members.iter_mut().filter(|s| s.is_empty()) {
+            sets.push(rng.gen_range(0..self.num_sets));
+        }
+
+        let mut problem = ProblemVariables::new();
+        let vars = binaries(&mut problem, self.num_sets);
+        let constraints = members.iter()
+            .map(|sets| constraint!(sets.iter().map(|s| vars[*s]).sum::<Expression>() >= 1))
+            .collect();
+
+        MipModel {
+            problem: problem.minimise(weighted_sum(&costs, &vars)),
+            constraints,
+            variables: vars,
+        }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::SetCover(self.clone())
+    }
+}
+
+impl ModelBuilder for AssignmentSpec {
+    /// The variables are laid out agent by agent, panics without agents or for a negative capacity ratio
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        assert!(self.num_agents >= 1, "assignment needs at least one agent");
+        assert!(self.capacity_ratio >= 0. && self.capacity_ratio.is_finite(),
+            "capacity ratio {} is not a non-negative number", self.capacity_ratio);
+        let mut problem = ProblemVariables::new();
+        let assignment: Vec<_> = (0..self.num_agents)
+            .map(|_| binaries(&mut problem, self.num_jobs))
+            .collect();
+
+        let mut objective = Expression::from(0);
+        let mut constraints: Vec<_> = (0..self.num_jobs)
+            .map(|j| constraint!(assignment.iter().map(|row| row[j]).sum::<Expression>() == 1))
+            .collect();
+        for row in &assignment {
+            let costs = random_values(self.num_jobs, 10, 50, rng);
+            let needs = random_values(self.num_jobs, 5, 25, rng);
+            let capacity = (self.capacity_ratio * needs.iter().sum::<f64>() / self.num_agents as f64).floor();
+            objective += weighted_sum(&costs, row);
+            constraints.push(constraint!(weighted_sum(&needs, row) <= capacity));
+        }
+
+        MipModel {
+            problem: problem.minimise(objective),
+            constraints,
+            variables: assignment.into_iter().flatten().collect(),
+        }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::Assignment(self.clone())
+    }
+}
+
+impl ModelBuilder for FacilityLocationSpec {
+    /// Serving costs are the distances, the variables are the facilities followed by the
+    /// continuous shares of each customer served by the facilities.
+    /// Panics without facilities or for a negative opening cost.
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        assert!(self.num_facilities >= 1, "facility location needs at least one facility");
+        assert!(self.opening_cost >= 0. && self.opening_cost.is_finite(),
+            "opening cost {} is not a non-negative number", self.opening_cost);
+        let mut point = || (rng.gen::<f64>(), rng.gen::<f64>());
+        let facilities: Vec<_> = (0..self.num_facilities).map(|_| point()).collect();
+        let customers: Vec<_> = (0..self.num_customers).map(|_| point()).collect();
+
+        let mut problem = ProblemVariables::new();
+        let open = binaries(&mut problem, self.num_facilities);
+        let serve: Vec<Vec<Variable>> = (0..self.num_customers)
+            .map(|_| (0..self.num_facilities).map(|_| problem.add(variable().min(0).max(1))).collect())
+            .collect();
+
+        let mut objective: Expression = open.iter().map(|y| self.opening_cost * *y).sum();
+        let mut constraints = Vec::new();
+        for (customer, row) in customers.iter().zip(&serve) {
+            constraints.push(constraint!(row.iter().sum::<Expression>() == 1));
+            for ((facility, y), x) in facilities.iter().zip(&open).zip(row) {
+                let distance = ((customer.0 - facility.0).powi(2) + (customer.1 - facility.1).powi(2)).sqrt();
+                objective += distance * *x;
+                constraints.push(constraint!(*x <= *y));
+            }
+        }
+
+        MipModel {
+            problem: problem.minimise(objective),
+            constraints,
+            variables: open.into_iter().chain(serve.into_iter().flatten()).collect(),
+        }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        ModelSpec::FacilityLocation(self.clone())
+    }
+}
+
+impl ModelBuilder for ModelSpec {
+    fn build(&self, rng: &mut StdRng) -> MipModel {
+        match self {
+            ModelSpec::Knapsack(spec) => spec.build(rng),
+            ModelSpec::MultiKnapsack(spec) => spec.build(rng),
+            ModelSpec::BinPacking(spec) => spec.build(rng),
+            ModelSpec::SetCover(spec) => spec.build(rng),
+            ModelSpec::Assignment(spec) => spec.build(rng),
+            ModelSpec::FacilityLocation(spec) => spec.build(rng),
+        }
+    }
+
+    fn spec(&self) -> ModelSpec {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use good_lp::{IntoAffineExpression, SolverModel, Solution};
+    use rand::{SeedableRng, rngs::StdRng};
+    use crate::custom_solver::CustomSolver;
+    use crate::solution::CustomSolution;
+    use crate::validation::check_solution;
+    use super::*;
+
+    fn solve(model: &MipModel) -> CustomSolution {
+        let mut solver = model.problem.clone().using(CustomSolver::new);
+        for c in &model.constraints {
+            solver = solver.with(c.clone());
+        }
+        let solution = solver.solve().unwrap();
+        CustomSolution::new(model.variables.iter().map(|v| solution.value(*v)).collect())
+    }
+
+    #[test]
+    fn test_models_feasible() {
+        let specs = [
+            ModelSpec::Knapsack(KnapsackSpec{ num_items: 8, max_value: 20, capacity_ratio: 0.5 }),
+            ModelSpec::MultiKnapsack(MultiKnapsackSpec{ num_items: 6, num_dimensions: 3, max_value: 20, capacity_ratio: 0.5 }),
+            ModelSpec::BinPacking(BinPackingSpec{ num_items: 4, capacity: 10, min_size: 3, max_size: 7 }),
+            ModelSpec::SetCover(SetCoverSpec{ num_elements: 6, num_sets: 5, density: 0.3, max_cost: 10 }),
+            ModelSpec::Assignment(AssignmentSpec{ num_agents: 2, num_jobs: 4, capacity_ratio: 1.5 }),
+            ModelSpec::FacilityLocation(FacilityLocationSpec{ num_facilities: 3, num_customers: 4, opening_cost: 0.5 }),
+        ];
+        for spec in specs {
+            let model = spec.build(&mut StdRng::seed_from_u64(1));
+            assert_eq!(model.variables.len(), model.problem.variables.len());
+
+            let int_vars: HashSet<_> = model.problem.variables.iter_variables_with_def()
+                .filter(|(_, def)| def.is_integer)
+                .map(|(var, _)| var)
+                .collect();
+            let solution = solve(&model);
+            assert_eq!(check_solution(&model.problem, &model.constraints, &int_vars, &solution), Ok(()), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn test_knapsack_optimal() {
+        let spec = KnapsackSpec{ num_items: 8, max_value: 20, capacity_ratio: 0.4 };
+        let model = spec.build(&mut StdRng::seed_from_u64(7));
+        let objective = &model.problem.objective;
+
+        // Best of all 2^8 assignments
+        let best = (0..1u32 << 8)
+            .map(|mask| CustomSolution::new((0..8).map(|i| (mask >> i & 1) as f64).collect()))
+            .filter(|s| model.constraints.iter().all(|c| c.expression.clone().eval_with(s) <= 1e-9))
+            .map(|s| objective.eval_with(&s))
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        assert!((objective.eval_with(&solve(&model)) - best).abs() < 1e-6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_size_range() {
+        BinPackingSpec{ num_items: 4, capacity: 10, min_size: 7, max_size: 3 }.build(&mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_density_above_one() {
+        SetCoverSpec{ num_elements: 6, num_sets: 5, density: 1.5, max_cost: 10 }.build(&mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_cover_without_sets() {
+        SetCoverSpec{ num_elements: 6, num_sets: 0, density: 0.3, max_cost: 10 }.build(&mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assignment_without_agents() {
+        AssignmentSpec{ num_agents: 0, num_jobs: 4, capacity_ratio: 1.5 }.build(&mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_knapsack_zero_values() {
+        KnapsackSpec{ num_items: 8, max_value: 0, capacity_ratio: 0.5 }.build(&mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_seeded() {
+        let spec = SetCoverSpec{ num_elements: 10, num_sets: 6, density: 0.2, max_cost: 10 };
+        let first = spec.build(&mut StdRng::seed_from_u64(3));
+        let second = spec.build(&mut StdRng::seed_from_u64(3));
+        let evaluated = |model: &MipModel| model.constraints.iter()
+            .map(|c| c.expression.clone().eval_with(&CustomSolution::new(vec![1.; 6])))
+            .collect::<Vec<_>>();
+        assert_eq!(evaluated(&first), evaluated(&second));
+
+        let json = serde_json::to_string(&spec.spec()).unwrap();
+        assert_eq!(serde_json::from_str::<ModelSpec>(&json).unwrap(), ModelSpec::SetCover(spec));
+    }
+}
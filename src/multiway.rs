@@ -0,0 +1,289 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use good_lp::{constraint, variable, variable::UnsolvedProblem, Constraint, Expression, ProblemVariables, Variable};
+use serde::Serialize;
+use crate::solution::CustomSolution;
+
+/// What the k-way partitioning model minimises
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum MultiwayObjective {
+    /// Largest minus smallest subset sum
+    Range,
+    /// Largest subset sum
+    LargestSum,
+}
+
+/// Constructive heuristics for k-way partitioning
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum MultiwayHeuristic {
+    /// Longest processing time first, each item goes to the subset with the smallest sum
+    Lpt,
+    KarmarkarKarp,
+}
+
+/// Assignment model of k-way partitioning.
+/// The variables are laid out item by item as `x[i][j]`, followed by `largest` and `smallest`.
+pub struct MultiwayModel {
+    pub problem: UnsolvedProblem,
+    pub constraints: Vec<Constraint>,
+    /// Binary `assignment[i][j]` puts item `i` into subset `j`
+    pub assignment: Vec<Vec<Variable>>,
+    pub largest: Variable,
+    /// Only bounded from above, so it is not the smallest sum for `LargestSum` in general
+    pub smallest: Variable,
+}
+
+impl MultiwayModel {
+    /// Subsets are numbered by their first item, so item `i` can only go to subsets `0..=i`.
+    /// Panics without subsets.
+    pub fn new(weights: &[f64], num_subsets: usize, objective: MultiwayObjective) -> Self {
+        assert!(num_subsets >= 1, "partitioning needs at least one subset");
+        let mut problem = ProblemVariables::new();
+        let assignment: Vec<Vec<Variable>> = (0..weights.len())
+            .map(|i| (0..num_subsets)
+                .map(|j| problem.add(variable().integer().min(0).max(if j <= i { 1 } else { 0 })))
+                .collect())
+            .collect();
+        let largest = problem.add(variable());
+        let smallest = problem.add(variable());
+
+        let mut constraints: Vec<_> = assignment.iter()
+            .map(|row| constraint!(row.iter().sum::<Expression>() == 1))
+            .collect();
+        for j in 0..num_subsets {
+            let sum: Expression = weights.iter()
+                .zip(&assignment)
+                .map(|(w, row)| *w * row[j])
+                .sum();
+            constraints.push(constraint!(sum.clone() <= largest));
+            constraints.push(constraint!(sum >= smallest));
+        }
+
+        let problem = match objective {
+            MultiwayObjective::Range => problem.minimise(largest - smallest),
+            MultiwayObjective::LargestSum => problem.minimise(largest),
+        };
+
+        MultiwayModel { problem, constraints, assignment, largest, smallest }
+    }
+
+    pub fn num_subsets(&self) -> usize {
+        self.assignment.first().map_or(0, Vec::len)
+    }
+
+    /// All variables in the order of the layout
+    pub fn variables(&self) -> Vec<Variable> {
+        self.assignment.iter().flatten().copied()
+            .chain([self.largest, self.smallest])
+            .collect()
+    }
+
+    /// Values of the model for the subset of each item, relabelled to satisfy the symmetry breaking
+    pub fn values(&self, weights: &[f64], subsets: &[usize]) -> Vec<f64> {
+        let k = self.num_subsets();
+        let mut labels = HashMap::new();
+        let subsets: Vec<_> = subsets.iter()
+            .map(|s| {
+                let next = labels.len();
+                *labels.entry(*s).or_insert(next)
+            })
+            .collect();
+
+        let sums = subset_sums(weights, k, &subsets);
+        let mut values = vec![0.; weights.len() * k];
+        for (i, &s) in subsets.iter().enumerate() {
+            values[i * k + s] = 1.;
+        }
+        values.push(sums.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+        values.push(sums.iter().copied().fold(f64::INFINITY, f64::min));
+        values
+    }
+
+    pub fn solution_from_heuristic(&self, weights: &[f64], heuristic: MultiwayHeuristic) -> CustomSolution {
+        let subsets = match heuristic {
+            MultiwayHeuristic::Lpt => lpt_heuristic(weights, self.num_subsets()),
+            MultiwayHeuristic::KarmarkarKarp => multiway_karmarkar_karp(weights, self.num_subsets()),
+        };
+        CustomSolution::new(self.values(weights, &subsets))
+    }
+}
+
+/// Sums of the `k` subsets, `subsets[i]` is the subset of item `i`
+pub fn subset_sums(weights: &[f64], k: usize, subsets: &[usize]) -> Vec<f64> {
+    let mut sums = vec![0.; k];
+    for (w, &s) in weights.iter().zip(subsets) {
+        sums[s] += w;
+    }
+    sums
+}
+
+/// Subset of each item, largest items first into the subset with the smallest sum, panics for `k == 0`
+pub fn lpt_heuristic(weights: &[f64], k: usize) -> Vec<usize> {
+    assert!(k >= 1, "partitioning needs at least one subset");
+    let mut idx_sorted: Vec<_> = (0..weights.len()).collect();
+    idx_sorted.sort_by(|&i, &j| weights[j].total_cmp(&weights[i]));
+
+    let mut sums = vec![0_f64; k];
+    let mut subsets = vec![0; weights.len()];
+    for idx in idx_sorted {
+        let smallest = (0..k).min_by(|&a, &b| sums[a].total_cmp(&sums[b])).unwrap();
+        sums[smallest] += weights[idx];
+        subsets[idx] = smallest;
+    }
+    subsets
+}
+
+/// Partial partition of the differencing, the slots are sorted by decreasing sum
+struct KKTuple {
+    slots: Vec<(f64, Vec<usize>)>,
+    id: usize,
+}
+
+impl KKTuple {
+    fn spread(&self) -> f64 {
+        self.slots[0].0 - self.slots[self.slots.len()-1].0
+    }
+}
+
+impl PartialEq for KKTuple {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for KKTuple {}
+
+impl PartialOrd for KKTuple {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KKTuple {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.spread().total_cmp(&other.spread())
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// Subset of each item from differencing the k-tuples with the largest spreads, panics for `k == 0`
+pub fn multiway_karmarkar_karp(weights: &[f64], k: usize) -> Vec<usize> {
+    assert!(k >= 1, "partitioning needs at least one subset");
+    let mut heap: BinaryHeap<_> = weights.iter().enumerate()
+        .map(|(idx, &w)| {
+            let mut slots = vec![(0., Vec::new()); k];
+            slots[0] = (w, vec![idx]);
+            KKTuple{ slots, id: idx }
+        })
+        .collect();
+
+    // Combining the largest slot of one tuple with the smallest of the other evens out the sums
+    let mut next_id = weights.len();
+    while heap.len() > 1 {
+        let large = heap.pop().unwrap();
+        let small = heap.pop().unwrap();
+        let mut slots: Vec<_> = large.slots.into_iter()
+            .zip(small.slots.into_iter().rev())
+            .map(|((s1, mut items1), (s2, items2))| {
+                items1.extend(items2);
+                (s1 + s2, items1)
+            })
+            .collect();
+        slots.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let min = slots[k-1].0;
+        slots.iter_mut().for_each(|s| s.0 -= min);
+
+        heap.push(KKTuple{ slots, id: next_id });
+        next_id += 1;
+    }
+
+    let mut subsets = vec![0; weights.len()];
+    for (j, (_, items)) in heap.pop().into_iter().flat_map(|t| t.slots).enumerate() {
+        items.into_iter().for_each(|i| subsets[i] = j);
+    }
+    subsets
+}
+
+#[cfg(test)]
+mod tests {
+    use good_lp::{SolverModel, Solution};
+    use crate::custom_solver::CustomSolver;
+    use super::{MultiwayModel, MultiwayObjective, MultiwayHeuristic, lpt_heuristic, multiway_karmarkar_karp, subset_sums};
+
+    fn range(weights: &[f64], k: usize, subsets: &[usize]) -> f64 {
+        let sums = subset_sums(weights, k, subsets);
+        sums.iter().copied().fold(f64::NEG_INFINITY, f64::max) - sums.iter().copied().fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_lpt() {
+        // 8 | 7, 4 | 6, 5
+        let weights = [8., 7., 6., 5., 4.];
+        assert_eq!(lpt_heuristic(&weights, 3), vec![0, 1, 2, 2, 1]);
+        assert_eq!(lpt_heuristic(&weights, 1), vec![0; 5]);
+    }
+
+    #[test]
+    fn test_multiway_karmarkar_karp() {
+        // Differencing ends with {8}, {7, 4}, {6, 5}
+        let weights = [8., 7., 6., 5., 4.];
+        let subsets = multiway_karmarkar_karp(&weights, 3);
+        assert!((range(&weights, 3, &subsets) - 3.).abs() < 1e-6);
+        assert_eq!(subsets[1], subsets[4]);
+        assert_eq!(subsets[2], subsets[3]);
+
+        // Two-way differencing of {10, 8} vs {9, 7, 6}
+        let weights = [10., 9., 8., 7., 6.];
+        assert!((range(&weights, 2, &multiway_karmarkar_karp(&weights, 2)) - 4.).abs() < 1e-6);
+        assert!(multiway_karmarkar_karp(&[], 3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_subsets() {
+        multiway_karmarkar_karp(&[8., 7.], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_model_without_subsets() {
+        MultiwayModel::new(&[8., 7.], 0, MultiwayObjective::Range);
+    }
+
+    #[test]
+    fn test_symmetry_breaking() {
+        let weights = [8., 7., 6., 5., 4.];
+        let model = MultiwayModel::new(&weights, 3, MultiwayObjective::Range);
+        let max_bounds: Vec<_> = model.problem.variables.variables[..6].iter().map(|d| d.max).collect();
+        assert_eq!(max_bounds, vec![1., 0., 0., 1., 1., 0.]);
+
+        // Subsets are relabelled in the order of their first item
+        let values = model.values(&weights, &[2, 0, 1, 1, 0]);
+        assert_eq!(values[..6], [1., 0., 0., 0., 1., 0.]);
+        assert_eq!(values[15..], [11., 8.]);
+    }
+
+    #[test]
+    fn test_multiway_model() {
+        let weights = [8., 7., 6., 5., 4.];
+        for (objective, optimum) in [(MultiwayObjective::Range, 3.), (MultiwayObjective::LargestSum, 11.)] {
+            let model = MultiwayModel::new(&weights, 3, objective);
+            let start = model.solution_from_heuristic(&weights, MultiwayHeuristic::Lpt);
+            let mut solver = model.problem.clone().using(CustomSolver::new)
+                .with_mip_start(model.variables().into_iter().zip(start.values().iter().copied()).collect());
+            for c in &model.constraints {
+                solver = solver.with(c.clone());
+            }
+            let solution = solver.solve().unwrap();
+
+            let value = match objective {
+                MultiwayObjective::Range => solution.value(model.largest) - solution.value(model.smallest),
+                MultiwayObjective::LargestSum => solution.value(model.largest),
+            };
+            assert!((value - optimum).abs() < 1e-6);
+            for row in &model.assignment {
+                assert!((row.iter().map(|v| solution.value(*v)).sum::<f64>() - 1.).abs() < 1e-6);
+            }
+        }
+    }
+}
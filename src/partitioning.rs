@@ -0,0 +1,305 @@
+use std::time::{Duration, Instant};
+use serde::Serialize;
+
+const TOL: f64 = 1e-9;
+/// Largest magnitude up to which every integer is exactly representable as `f64`
+const MAX_EXACT_INT: f64 = (1u64 << 53) as f64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum PartitionAlgorithm {
+    CompleteKarmarkarKarp,
+    CompleteGreedy,
+}
+
+#[derive(Debug, Clone)]
+pub struct PartitionResult {
+    /// Assignment of the weights followed by the difference, same layout as the start heuristics
+    pub values: Vec<f64>,
+    pub num_nodes: usize,
+    /// False if a limit stopped the search before it was exhausted
+    pub optimal: bool,
+}
+
+/// Anytime exact solver for two-way number partitioning.
+/// The first solution found is the Karmarkar-Karp resp. greedy one,
+/// further improvements are found until a limit is hit or optimality is proven.
+pub struct PartitionSolver {
+    algorithm: PartitionAlgorithm,
+    max_nodes: Option<usize>,
+    time_limit: Option<Duration>,
+}
+
+impl PartitionSolver {
+    pub fn new(algorithm: PartitionAlgorithm) -> Self {
+        PartitionSolver {
+            algorithm,
+            max_nodes: None,
+            time_limit: None,
+        }
+    }
+
+    pub fn with_node_limit(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn solve(&self, weights: &[f64]) -> PartitionResult {
+        let mut search = Search::new(weights, self.max_nodes, self.time_limit);
+
+        match self.algorithm {
+            PartitionAlgorithm::CompleteKarmarkarKarp => {
+                let mut subsets: Vec<_> = weights.iter().enumerate()
+                    .map(|(i, &value)| Subset{ value, items: vec![(i, true)] })
+                    .collect();
+                subsets.sort_by(|a, b| b.value.total_cmp(&a.value));
+                search.ckk(subsets);
+            },
+            PartitionAlgorithm::CompleteGreedy => {
+                let mut order: Vec<_> = (0..weights.len()).collect();
+                order.sort_by(|&i, &j| weights[j].total_cmp(&weights[i]));
+                let mut sides = vec![false; weights.len()];
+                search.cga(&order, 0, 0., 0., weights.iter().sum(), &mut sides);
+            },
+        }
+
+        let mut values: Vec<_> = search.best_sides.iter()
+            .map(|&side| side as i32 as f64)
+            .collect();
+        values.push(search.best_diff);
+
+        PartitionResult {
+            values,
+            num_nodes: search.num_nodes,
+            optimal: !search.stopped,
+        }
+    }
+}
+
+/// Weights already combined by differencing or summing.
+/// Items on side `true` minus items on side `false` sum up to `value`.
+struct Subset {
+    value: f64,
+    items: Vec<(usize, bool)>,
+}
+
+struct Search<'a> {
+    weights: &'a [f64],
+    best_diff: f64,
+    best_sides: Vec<bool>,
+    /// Parity of the total for integer weights, no partition can be better
+    lower_bound: f64,
+    num_nodes: usize,
+    max_nodes: Option<usize>,
+    deadline: Option<Instant>,
+    stopped: bool,
+}
+
+impl<'a> Search<'a> {
+    fn new(weights: &'a [f64], max_nodes: Option<usize>, time_limit: Option<Duration>) -> Self {
+        // Summed on integers, as sums above `MAX_EXACT_INT` are rounded to even numbers
+        let lower_bound = if weights.iter().all(|w| w.fract() == 0. && w.abs() <= MAX_EXACT_INT) {
+            weights.iter().map(|w| *w as i128).sum::<i128>().rem_euclid(2) as f64
+        } else {
+            0.
+        };
+
+        Search {
+            weights,
+            best_diff: f64::INFINITY,
+            best_sides: vec![false; weights.len()],
+            lower_bound,
+            num_nodes: 0,
+            max_nodes,
+            deadline: time_limit.map(|limit| Instant::now() + limit),
+            stopped: false,
+        }
+    }
+
+    /// Counts the node and checks the limits, which only apply once a solution is known
+    fn should_stop(&mut self) -> bool {
+        if self.stopped || self.best_diff <= self.lower_bound + TOL {
+            return true;
+        }
+        self.num_nodes += 1;
+
+        if self.best_diff.is_finite() {
+            let nodes_exceeded = self.max_nodes.is_some_and(|max| self.num_nodes > max);
+            let time_exceeded = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            self.stopped = nodes_exceeded || time_exceeded;
+        }
+        self.stopped
+    }
+
+    fn update(&mut self, sides: Vec<bool>) {
+        let diff = self.weights.iter().zip(&sides)
+            .map(|(w, &side)| if side { *w } else { -w })
+            .sum::<f64>().abs();
+
+        if diff < self.best_diff {
+            self.best_diff = diff;
+            self.best_sides = sides;
+        }
+    }
+
+    /// Subsets are sorted by decreasing value
+    fn ckk(&mut self, mut subsets: Vec<Subset>) {
+        if self.should_stop() {
+            return;
+        }
+
+        let rest: f64 = subsets.iter().skip(1).map(|s| s.value).sum();
+        if subsets.is_empty() || subsets[0].value >= rest {
+            // Best possible is the largest against all others
+            let mut sides = vec![false; self.weights.len()];
+            for (k, subset) in subsets.iter().enumerate() {
+                for &(i, side) in &subset.items {
+                    sides[i] = side == (k == 0);
+                }
+            }
+            self.update(sides);
+            return;
+        }
+
+        let large = subsets.remove(0);
+        let small = subsets.remove(0);
+
+        // Differencing puts the two subsets on opposite sides
+        let mut difference = subsets.iter()
+            .map(|s| Subset{ value: s.value, items: s.items.clone() })
+            .collect::<Vec<_>>();
+        let items = large.items.iter().copied()
+            .chain(small.items.iter().map(|&(i, side)| (i, !side)))
+            .collect();
+        let value = large.value - small.value;
+        let pos = difference.iter().position(|s| s.value < value).unwrap_or(difference.len());
+        difference.insert(pos, Subset{ value, items });
+        self.ckk(difference);
+
+        // Summing puts them on the same side, the new subset is the largest
+        let items = large.items.into_iter().chain(small.items).collect();
+        subsets.insert(0, Subset{ value: large.value + small.value, items });
+        self.ckk(subsets);
+    }
+
+    /// Assigns the weights in `order` from `pos` on, the smaller side is tried first
+    fn cga(&mut self, order: &[usize], pos: usize, sum_left: f64, sum_right: f64, remaining: f64, sides: &mut [bool]) {
+        if self.should_stop() {
+            return;
+        }
+
+        // `remaining` may be slightly above zero after the last item due to rounding
+        let right_smaller = sum_right < sum_left;
+        if pos == order.len() || (sum_left - sum_right).abs() >= remaining {
+            // Everything left goes to the smaller side
+            let mut sides = sides.to_vec();
+            order[pos..].iter().for_each(|&i| sides[i] = right_smaller);
+            self.update(sides);
+            return;
+        }
+
+        let idx = order[pos];
+        let weight = self.weights[idx];
+        for side in [right_smaller, !right_smaller] {
+            sides[idx] = side;
+            let (left, right) = if side { (sum_left, sum_right + weight) } else { (sum_left + weight, sum_right) };
+            self.cga(order, pos + 1, left, right, remaining - weight, sides);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PartitionSolver, PartitionAlgorithm, Search, MAX_EXACT_INT};
+
+    const ALGORITHMS: [PartitionAlgorithm; 2] = [
+        PartitionAlgorithm::CompleteKarmarkarKarp,
+        PartitionAlgorithm::CompleteGreedy,
+    ];
+
+    fn diff(weights: &[f64], values: &[f64]) -> f64 {
+        weights.iter().zip(values)
+            .map(|(w, v)| (2. * v - 1.) * w)
+            .sum::<f64>().abs()
+    }
+
+    #[test]
+    fn test_perfect_partition() {
+        // Karmarkar-Karp and greedy alone both end with a difference of 2
+        let weights = [8., 7., 6., 5., 4.];
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).solve(&weights);
+            assert!(result.optimal);
+            assert!(result.values[5] < 1e-6);
+            assert!(diff(&weights, &result.values) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_odd_total() {
+        let weights = [10., 9., 8., 7., 6.];
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).solve(&weights);
+            assert!(result.optimal);
+            assert!((result.values[5] - 2.).abs() < 1e-6);
+            assert!((diff(&weights, &result.values) - 2.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_fractional() {
+        let weights = [0.5, 1.25, 2.5, 0.75, 3.3];
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).solve(&weights);
+            assert!(result.optimal);
+            assert!((result.values[5] - 0.2).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_rounding_at_leaf() {
+        // The remaining sum ends slightly above zero after the last item
+        let weights = [0.55, 0.8, 0.9, 0.15, 0.8];
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).solve(&weights);
+            assert!(diff(&weights, &result.values) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let weights: Vec<_> = (1..=30).map(|i| (i as f64).sqrt()).collect();
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).with_node_limit(5).solve(&weights);
+            assert!(!result.optimal);
+            // The limit only applies once the first solution is found
+            assert!(result.num_nodes <= weights.len() + 1);
+            assert_eq!(result.values.len(), weights.len() + 1);
+            assert!((diff(&weights, &result.values) - result.values[30]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_trivial() {
+        for algorithm in ALGORITHMS {
+            let result = PartitionSolver::new(algorithm).solve(&[]);
+            assert_eq!(result.values, vec![0.]);
+
+            let result = PartitionSolver::new(algorithm).solve(&[4.]);
+            assert!((result.values[1] - 4.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        // The sum rounds to 2^53 as f64
+        assert_eq!(Search::new(&[MAX_EXACT_INT, 1.], None, None).lower_bound, 1.);
+        assert_eq!(Search::new(&[MAX_EXACT_INT, 1., 1.], None, None).lower_bound, 0.);
+        assert_eq!(Search::new(&[-3., 4.], None, None).lower_bound, 1.);
+        assert_eq!(Search::new(&[1.5, 2.], None, None).lower_bound, 0.);
+    }
+}
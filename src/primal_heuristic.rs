@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+use good_lp::{variable::UnsolvedProblem, Constraint, Variable};
+use crate::custom_solver::LpSolveFn;
+use crate::mip_heuristics::{MipContext, MipHeuristic};
+use crate::solution::CustomSolution;
+
+/// When `CustomSolver` calls a registered heuristic
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HeuristicTiming {
+    Root,
+    /// At the root and then every `k` nodes
+    EveryNodes(usize),
+    /// Whenever the incumbent was replaced by a better solution
+    OnNewIncumbent,
+}
+
+/// State of the branch-and-bound tree passed to the heuristics
+pub struct HeuristicContext<'a> {
+    /// Relaxed problem, the integer variables are listed in `int_vars`
+    pub problem: &'a UnsolvedProblem,
+    /// Constraints of the current node, including the branching constraints
+    pub constraints: &'a [Constraint],
+    pub int_vars: &'a HashSet<Variable>,
+    /// Solution of the relaxation at the current node
+    pub relaxation: &'a CustomSolution,
+    pub incumbent: Option<&'a CustomSolution>,
+    pub num_nodes: usize,
+    pub(crate) solve_lp: &'a LpSolveFn,
+}
+
+impl HeuristicContext<'_> {
+    /// Solves a relaxation with the LP backend of the solver
+    pub fn solve_lp(&self, problem: UnsolvedProblem, constraints: &[Constraint]) -> Option<CustomSolution> {
+        (self.solve_lp)(problem, constraints).ok().map(|(solution, _)| solution)
+    }
+}
+
+/// Heuristics that can be registered on `CustomSolver`.
+/// Returned solutions are checked against the model and only kept if better than the incumbent.
+pub trait PrimalHeuristic {
+    fn find_solution(&mut self, context: &HeuristicContext) -> Option<CustomSolution>;
+}
+
+impl PrimalHeuristic for MipHeuristic {
+    fn find_solution(&mut self, context: &HeuristicContext) -> Option<CustomSolution> {
+        MipContext {
+            problem: context.problem,
+            constraints: context.constraints,
+            int_vars: context.int_vars,
+            solve_lp: context.solve_lp,
+            incumbent: context.incumbent,
+        }.run(*self, context.relaxation)
+    }
+}
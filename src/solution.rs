@@ -1,8 +1,21 @@
-use good_lp::{Solution, Variable, solvers::highs::HighsSolution};
+use std::collections::HashMap;
+use num_rational::BigRational;
+use good_lp::{Solution, Variable, Constraint, IntoAffineExpression,
+    constraint::ConstraintReference, solvers::highs::HighsSolution};
+use crate::validation::StartOutcome;
 
+#[derive(Clone)]
 pub struct CustomSolution {
     pub num_evals: usize,
     values: Vec<f64>,
+    /// Duals of the LP relaxation at the node where the solution was found
+    pub duals: Option<LpDuals>,
+    /// Set if the start solution did not fit the model
+    pub start: Option<StartOutcome>,
+    /// Set if the MIP start was rejected
+    pub mip_start: Option<StartOutcome>,
+    /// Heuristic solutions that did not fit the model
+    pub num_rejected: usize,
 }
 
 impl CustomSolution {
@@ -10,11 +23,19 @@ impl CustomSolution {
         CustomSolution{
             values,
             num_evals: 0,
+            duals: None,
+            start: None,
+            mip_start: None,
+            num_rejected: 0,
         }
 
     }
 
-    pub fn from(solution: &HighsSolution, num_vars: usize, num_evals: usize) -> Self {
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn from(solution: &impl Solution, num_vars: usize, num_evals: usize) -> Self {
         let values = (0..num_vars)
             .map(|i| Variable::new(i))
             .map(|v| solution.value(v))
@@ -23,6 +44,10 @@ impl CustomSolution {
         CustomSolution{
             values,
             num_evals,
+            duals: None,
+            start: None,
+            mip_start: None,
+            num_rejected: 0,
         }
     }
 }
@@ -32,3 +57,102 @@ impl Solution for CustomSolution {
         self.values[variable.index()]
     }
 }
+
+pub struct MySolution {
+    pub variable_values: HashMap<Variable, f64>,
+    pub duals: LpDuals,
+    /// Not available when solved in exact arithmetic only
+    pub ranging: Option<Ranging>,
+    /// Only available when solved with exact arithmetic
+    pub exact_values: Option<HashMap<Variable, BigRational>>,
+    pub exact_objective: Option<BigRational>,
+}
+
+impl MySolution {
+    pub fn exact_value(&self, variable: Variable) -> Option<&BigRational> {
+        self.exact_values.as_ref()?.get(&variable)
+    }
+}
+
+impl Solution for MySolution {
+    fn value(&self, variable: Variable) -> f64 {
+        // Every variable of the solved problem has a value, others default to 0
+        *self.variable_values.get(&variable).unwrap_or(&0.)
+    }
+}
+
+/// Dual information of an LP solution. Duals are the change of the optimal objective
+/// per unit increase of a constraint's right-hand side, slacks are `rhs - lhs`
+/// and reduced costs are `c_j - duals^T A_j` over all constraints.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LpDuals {
+    pub duals: Vec<f64>,
+    pub slacks: Vec<f64>,
+    pub reduced_costs: HashMap<Variable, f64>,
+}
+
+impl LpDuals {
+    pub fn new(objective: &HashMap<Variable, f64>, constraints: &[Constraint], duals: Vec<f64>, solution: &impl Solution) -> Self {
+        let mut reduced_costs = objective.clone();
+        let mut slacks = Vec::new();
+        for (c, y) in constraints.iter().zip(&duals) {
+            slacks.push(-c.expression.clone().eval_with(solution));
+            for (var, a) in c.expression.clone().linear_coefficients() {
+                *reduced_costs.entry(var).or_insert(0.) -= y * a;
+            }
+        }
+
+        LpDuals {
+            duals,
+            slacks,
+            reduced_costs,
+        }
+    }
+
+    pub fn dual(&self, constraint: &ConstraintReference) -> f64 {
+        self.duals[constraint.index]
+    }
+
+    pub fn slack(&self, constraint: &ConstraintReference) -> f64 {
+        self.slacks[constraint.index]
+    }
+
+    pub fn reduced_cost(&self, variable: Variable) -> f64 {
+        *self.reduced_costs.get(&variable).unwrap_or(&0.)
+    }
+}
+
+/// Intervals of objective coefficients and constraint right-hand sides
+/// over which the optimal basis stays optimal, changing one value at a time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ranging {
+    pub objective: HashMap<Variable, (f64, f64)>,
+    pub rhs: Vec<(f64, f64)>,
+}
+
+impl Ranging {
+    pub fn objective_range(&self, variable: Variable) -> Option<(f64, f64)> {
+        self.objective.get(&variable).copied()
+    }
+
+    pub fn rhs_range(&self, constraint: &ConstraintReference) -> (f64, f64) {
+        self.rhs[constraint.index]
+    }
+}
+
+/// LP solutions that provide dual values, indexed like the constraints
+pub trait IntoDuals: Solution {
+    fn into_duals(self) -> Option<Vec<f64>>;
+}
+
+impl IntoDuals for HighsSolution {
+    fn into_duals(self) -> Option<Vec<f64>> {
+        Some(self.into_inner().dual_rows().to_vec())
+    }
+}
+
+impl IntoDuals for MySolution {
+    fn into_duals(self) -> Option<Vec<f64>> {
+        Some(self.duals.duals)
+    }
+}
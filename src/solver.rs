@@ -3,57 +3,170 @@ use std::collections::HashMap;
 use good_lp::{SolverModel, constraint::ConstraintReference, Constraint, 
     solvers::{ResolutionError, ObjectiveDirection}, variable::UnsolvedProblem, IntoAffineExpression, Variable };
 
-use crate::solution::MySolution;
-use crate::algorithm::solve_simplex;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use crate::solution::{MySolution, LpDuals, Ranging};
+use crate::algorithm::{solve_simplex_with, SimplexOptions};
+use crate::exact::{solve_exact, to_rational, Arithmetic};
 
 pub fn my_solver(to_solve: UnsolvedProblem) -> MySolver {
-    let err = match to_solve.direction {
-        ObjectiveDirection::Minimisation => Some(ResolutionError::Other("Minimization not valid")),
-        ObjectiveDirection::Maximisation => None,
+    // The simplex maximises, minimisation negates the objective
+    let sign = match to_solve.direction {
+        ObjectiveDirection::Minimisation => -1.,
+        ObjectiveDirection::Maximisation => 1.,
     };
 
-    let coeffs = to_solve.objective.linear_coefficients();
-    let mut table_map = HashMap::new();
-    for (var, c) in coeffs {
-        let var_coeffs = table_map.entry(var).or_insert(Vec::new());
-        var_coeffs.push(c);
+    let mut var_columns = Vec::new();
+    let mut num_cols = 0;
+    for (var, def) in to_solve.variables.iter_variables_with_def() {
+        let (offset, cols, upper) = match (def.min.is_finite(), def.max.is_finite()) {
+            // x = min + x', x' <= max - min
+            (true, true) => (def.min, vec![(num_cols, 1.)], Some(def.max - def.min)),
+            (true, false) => (def.min, vec![(num_cols, 1.)], None),
+            // x = max - x'
+            (false, true) => (def.max, vec![(num_cols, -1.)], None),
+            // x = x+ - x-
+            (false, false) => (0., vec![(num_cols, 1.), (num_cols+1, -1.)], None),
+        };
+        num_cols += cols.len();
+        var_columns.push(VarColumns { var, offset, cols, upper });
     }
 
+    let objective_constant = to_solve.objective.constant();
     MySolver {
-        table_map,
-        err,
-        slack_var_rows: Vec::new(),
-        rhs: vec![0.],
+        objective: to_solve.objective.linear_coefficients().collect(),
+        objective_constant,
+        sign,
+        var_columns,
+        num_cols,
+        constraints: Vec::new(),
+        arithmetic: Arithmetic::Float,
     }
 }
 
-pub struct MySolver {
-    table_map: HashMap<Variable, Vec<f64>>,
-    err: Option<ResolutionError>,
-    slack_var_rows: Vec<usize>,
-    rhs: Vec<f64>,
+/// Substitution `var = offset + sum(sign * column)` with non-negative columns
+struct VarColumns {
+    var: Variable,
+    offset: f64,
+    cols: Vec<(usize, f64)>,
+    /// Upper bound of the single column of bounded variables
+    upper: Option<f64>,
+}
+
+impl VarColumns {
+    fn value(&self, columns: &[f64]) -> f64 {
+        self.offset + self.cols.iter().map(|(col, s)| s * columns[*col]).sum::<f64>()
+    }
+
+    fn exact_value(&self, columns: &[BigRational]) -> BigRational {
+        self.cols.iter()
+            .map(|(col, s)| to_rational(*s) * &columns[*col])
+            .fold(to_rational(self.offset), |acc, v| acc + v)
+    }
+}
 
+pub struct MySolver {
+    objective: HashMap<Variable, f64>,
+    objective_constant: f64,
+    sign: f64,
+    var_columns: Vec<VarColumns>,
+    num_cols: usize,
+    constraints: Vec<Constraint>,
+    arithmetic: Arithmetic,
 }
 
 impl MySolver {
-    fn get_table(&self) -> (Vec<Vec<f64>>, Vec<usize>, Vec<&Variable>) {
+    pub fn with_arithmetic(mut self, arithmetic: Arithmetic) -> Self {
+        self.arithmetic = arithmetic;
+        self
+    }
+
+    /// Builds the column-wise tableau: an unused first column, one column per (split) variable,
+    /// the slack columns and the right-hand side. Row 0 holds the objective,
+    /// followed by the constraints in insertion order and the upper bound rows.
+    fn get_table(&self) -> (Vec<Vec<f64>>, Vec<usize>) {
+        let mut rows: Vec<(Vec<f64>, f64, bool)> = Vec::new();
+
+        for c in &self.constraints {
+            let mut row = vec![0.; self.num_cols];
+            let mut rhs = -c.expression.constant();
+            for (var, a) in c.expression.clone().linear_coefficients() {
+                let vc = &self.var_columns[var.index()];
+                rhs -= a * vc.offset;
+                for (col, s) in &vc.cols {
+                    row[*col] += a * s;
+                }
+            }
+            rows.push((row, rhs, c.is_equality));
+        }
+
+        for vc in &self.var_columns {
+            let Some(upper) = vc.upper else { continue };
+            let mut row = vec![0.; self.num_cols];
+            row[vc.cols[0].0] = 1.;
+            rows.push((row, upper, false));
+        }
+
+        let mut objective = vec![0.; self.num_cols];
+        for (var, c) in &self.objective {
+            for (col, s) in &self.var_columns[var.index()].cols {
+                objective[*col] += self.sign * c * s;
+            }
+        }
+
         let mut table = Vec::new();
-        table.push(vec![0.; self.rhs.len()]); // TODO: Remove first column (maybe)
-        let mut variables = Vec::new();
-        for (var, col) in &self.table_map {
-            table.push(col.clone());
-            variables.push(var);
+        table.push(vec![0.; rows.len()+1]); // TODO: Remove first column (maybe)
+        for j in 0..self.num_cols {
+            let mut col = vec![objective[j]];
+            col.extend(rows.iter().map(|(row, _, _)| row[j]));
+            table.push(col);
         }
 
-        for row in &self.slack_var_rows {
-            let mut slack_col = vec![0.; self.rhs.len()];
-            slack_col[*row] = 1.;
+        let slack_var_rows: Vec<usize> = (0..rows.len())
+            .filter(|&i| !rows[i].2)
+            .collect();
+        for row in &slack_var_rows {
+            let mut slack_col = vec![0.; rows.len()+1];
+            slack_col[row+1] = 1.;
             table.push(slack_col);
         }
-        table.push(self.rhs.clone());
-        let basic_var_cols = (self.table_map.len()..self.table_map.len()+self.slack_var_rows.len()).collect();
 
-        (table, basic_var_cols, variables)
+        let mut rhs = vec![0.];
+        rhs.extend(rows.iter().map(|(_, b, _)| *b));
+        table.push(rhs);
+        let basic_var_cols = (self.num_cols..self.num_cols+slack_var_rows.len()).collect();
+
+        (table, basic_var_cols)
+    }
+
+    /// Converts the column and row ranges of the tableau into intervals
+    /// of the objective coefficients and constraint right-hand sides
+    fn ranging(&self, cost_ranges: &[(f64, f64)], rhs_ranges: &[(f64, f64)]) -> Ranging {
+        let objective = self.var_columns.iter()
+            .map(|vc| {
+                // Tableau cost of a column changes by `sign * s` per unit of the coefficient,
+                // split variables have to stay within the ranges of both columns
+                let (lower, upper) = vc.cols.iter()
+                    .map(|(col, s)| {
+                        let (lower, upper) = cost_ranges[*col];
+                        let k = self.sign * s;
+                        if k > 0. { (lower / k, upper / k) } else { (upper / k, lower / k) }
+                    })
+                    .fold((f64::NEG_INFINITY, f64::INFINITY), |(l1, u1), (l2, u2)| (l1.max(l2), u1.min(u2)));
+                let c = *self.objective.get(&vc.var).unwrap_or(&0.);
+                (vc.var, (c + lower, c + upper))
+            })
+            .collect();
+
+        let rhs = self.constraints.iter().zip(rhs_ranges)
+            .map(|(c, (lower, upper))| {
+                let b = -c.expression.constant();
+                (b + lower, b + upper)
+            })
+            .collect();
+
+        Ranging { objective, rhs }
     }
 }
 
@@ -62,46 +175,69 @@ impl SolverModel for MySolver {
     type Error = ResolutionError;
 
     fn add_constraint(&mut self, c: Constraint) -> ConstraintReference {
-        self.rhs.push(-c.expression.constant());
-
-        let coeffs: HashMap<Variable, f64> = c.expression.linear_coefficients().collect();
-
-        for (k, v) in self.table_map.iter_mut() {
-            if !coeffs.contains_key(k) {
-                v.push(0.);
-            }
-        }
-
-        for (var, c) in coeffs {
-            let var_coeffs = self.table_map.get_mut(&var).unwrap();
-            var_coeffs.push(c);
-        }
-
-        if !c.is_equality {
-            self.slack_var_rows.push(self.rhs.len()-1);
-        }
-
-        ConstraintReference{index: self.rhs.len()-1}
+        self.constraints.push(c);
+        ConstraintReference{index: self.constraints.len()-1}
     }
 
     fn solve(self) -> Result<Self::Solution, Self::Error> {
-        if self.err.is_some() { return Err(self.err.unwrap()); }
+        let (table, basic_var_cols) = self.get_table();
+
+        let float = match self.arithmetic {
+            Arithmetic::Exact => None,
+            _ => Some(solve_simplex_with(&table, &basic_var_cols, &SimplexOptions::default())?),
+        };
+        let exact = match (self.arithmetic, &float) {
+            (Arithmetic::Exact, _) => Some(solve_exact(&table, &basic_var_cols, None)?),
+            (Arithmetic::Verified, Some(float)) => Some(solve_exact(&table, &basic_var_cols, Some(&float.basis))?),
+            _ => None,
+        };
 
-        let (table, basic_var_cols, variables) = self.get_table();
+        let (columns, row_duals): (Vec<f64>, Vec<f64>) = match (&exact, &float) {
+            (Some(exact), _) => (
+                exact.values.iter().map(|v| v.to_f64().unwrap()).collect(),
+                exact.duals.iter().map(|v| v.to_f64().unwrap()).collect(),
+            ),
+            (None, Some(float)) => (float.values.clone(), float.duals.clone()),
+            (None, None) => unreachable!(),
+        };
+        let sol_map: HashMap<Variable, f64> = self.var_columns.iter()
+            .map(|vc| (vc.var, vc.value(&columns)))
+            .collect();
 
-        let solution = solve_simplex(table, basic_var_cols);
-        let sol_map = solution.iter()
-            .map(|(k, v)| (*variables[*k as usize], *v))
+        // The tableau maximises `sign * objective`, the upper bound rows after the constraints are not reported
+        let row_duals = row_duals.iter()
+            .take(self.constraints.len())
+            .map(|y| self.sign * y)
             .collect();
+        let duals = LpDuals::new(&self.objective, &self.constraints, row_duals, &sol_map);
+        let ranging = float.map(|float| self.ranging(&float.cost_ranges, &float.rhs_ranges));
+
+        let exact_values: Option<HashMap<Variable, BigRational>> = exact.as_ref().map(|exact| self.var_columns.iter()
+            .map(|vc| (vc.var, vc.exact_value(&exact.values)))
+            .collect()
+        );
+
+        let exact_objective = exact_values.as_ref().map(|values| self.objective.iter()
+            .map(|(var, c)| to_rational(*c) * &values[var])
+            .fold(to_rational(self.objective_constant), |acc, v| acc + v)
+        );
 
-        Ok(MySolution{variable_values: sol_map})
+        Ok(MySolution{
+            variable_values: sol_map,
+            duals,
+            ranging,
+            exact_values,
+            exact_objective,
+        })
     }
 
 }
 
 #[cfg(test)]
 mod tests {
-    use good_lp::{ProblemVariables, variable, constraint, SolverModel};
+    use good_lp::{ProblemVariables, variable, constraint, SolverModel, Solution};
+    use num_rational::BigRational;
+    use crate::exact::Arithmetic;
     use super::my_solver;
 
     #[test]
@@ -115,15 +251,156 @@ mod tests {
             .with(constraint!(2*a + 4*b <= 16))
             .with(constraint!(3*a + 2*b <= 12));
 
-        let (table, basic_vars, vars) = problem.get_table();
+        let (table, basic_vars) = problem.get_table();
         assert_eq!(basic_vars, [2, 3]);
         assert_eq!(table[0], [0.; 3]);
-        assert!(table[1] == [7., 2., 3.] || table[1] == [6., 4., 2.]);
-        assert!(table[2] == [7., 2., 3.] || table[2] == [6., 4., 2.]);
+        assert_eq!(table[1], [7., 2., 3.]);
+        assert_eq!(table[2], [6., 4., 2.]);
         assert_eq!(table[3], [0., 1., 0.]);
         assert_eq!(table[4], [0., 0., 1.]);
         assert_eq!(table[5], [0., 16., 12.]);
-        assert!((*vars[0] == a && *vars[1] == b) || (*vars[0] == b && *vars[1] == a));
+        assert_eq!(problem.var_columns[0].var, a);
+        assert_eq!(problem.var_columns[1].var, b);
+    }
+
+    #[test]
+    fn test_beale() {
+        let mut problem = ProblemVariables::new();
+        let x4 = problem.add(variable().min(0));
+        let x5 = problem.add(variable().min(0));
+        let x6 = problem.add(variable().min(0));
+        let x7 = problem.add(variable().min(0));
+
+        let solution = problem.maximise(0.75*x4 - 20*x5 + 0.5*x6 - 6*x7)
+            .using(my_solver)
+            .with(constraint!(0.25*x4 - 8*x5 - x6 + 9*x7 <= 0))
+            .with(constraint!(0.5*x4 - 12*x5 - 0.5*x6 + 3*x7 <= 0))
+            .with(constraint!(x6 <= 1))
+            .solve().unwrap();
+
+        assert!((solution.value(x4) - 1.).abs() < 1e-6);
+        assert!((solution.value(x6) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_duals() {
+        let mut problem = ProblemVariables::new();
+        let a = problem.add(variable().min(0));
+        let b = problem.add(variable().min(0));
+
+        let mut model = problem.maximise(7*a + 6*b).using(my_solver);
+        let c1 = model.add_constraint(constraint!(2*a + 4*b <= 16));
+        let c2 = model.add_constraint(constraint!(3*a + 2*b <= 12));
+        let c3 = model.add_constraint(constraint!(a + b <= 10));
+        let solution = model.solve().unwrap();
+
+        assert!((solution.duals.dual(&c1) - 0.5).abs() < 1e-6);
+        assert!((solution.duals.dual(&c2) - 2.).abs() < 1e-6);
+        assert!(solution.duals.dual(&c3).abs() < 1e-6);
+        assert!(solution.duals.slack(&c1).abs() < 1e-6);
+        assert!((solution.duals.slack(&c3) - 5.).abs() < 1e-6);
+        assert!(solution.duals.reduced_cost(a).abs() < 1e-6);
+        assert!(solution.duals.reduced_cost(b).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_duals_minimisation() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().min(0));
+        let y = problem.add(variable().min(0));
+
+        // min 2x + 3y, x + y >= 4, x <= 3 -> x = 3, y = 1
+        let mut model = problem.minimise(2*x + 3*y).using(my_solver);
+        let c1 = model.add_constraint(constraint!(x + y >= 4));
+        let c2 = model.add_constraint(constraint!(x <= 3));
+        let solution = model.solve().unwrap();
+
+        assert!((solution.value(x) - 3.).abs() < 1e-6);
+        // `x + y >= 4` is stored as `4 - x - y <= 0`, raising its rhs relaxes the constraint
+        assert!((solution.duals.dual(&c1) + 3.).abs() < 1e-6);
+        assert!((solution.duals.dual(&c2) + 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ranging() {
+        let mut problem = ProblemVariables::new();
+        let a = problem.add(variable().min(0));
+        let b = problem.add(variable().min(0));
+
+        let mut model = problem.maximise(7*a + 6*b).using(my_solver);
+        let c1 = model.add_constraint(constraint!(2*a + 4*b <= 16));
+        let c2 = model.add_constraint(constraint!(3*a + 2*b <= 12));
+        let solution = model.solve().unwrap();
+        let ranging = solution.ranging.unwrap();
+
+        let (lower, upper) = ranging.objective_range(a).unwrap();
+        assert!((lower - 3.).abs() < 1e-6 && (upper - 9.).abs() < 1e-6);
+        let (lower, upper) = ranging.objective_range(b).unwrap();
+        assert!((lower - 14. / 3.).abs() < 1e-6 && (upper - 14.).abs() < 1e-6);
+        for c in [c1, c2] {
+            let (lower, upper) = ranging.rhs_range(&c);
+            assert!((lower - 8.).abs() < 1e-6 && (upper - 24.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_ranging_minimisation() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().min(0));
+        let y = problem.add(variable().min(0));
+
+        // min 2x + 3y, x + y >= 4, x <= 3 -> x = 3, y = 1 while c_x <= c_y
+        let mut model = problem.minimise(2*x + 3*y).using(my_solver);
+        model.add_constraint(constraint!(x + y >= 4));
+        model.add_constraint(constraint!(x <= 3));
+        let ranging = model.solve().unwrap().ranging.unwrap();
+
+        let (lower, upper) = ranging.objective_range(x).unwrap();
+        assert_eq!(lower, f64::NEG_INFINITY);
+        assert!((upper - 3.).abs() < 1e-6);
+        let (lower, upper) = ranging.objective_range(y).unwrap();
+        assert!((lower - 2.).abs() < 1e-6);
+        assert_eq!(upper, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_exact() {
+        let mut problem = ProblemVariables::new();
+        let a = problem.add(variable().min(0));
+        let b = problem.add(variable().min(0));
+
+        for arithmetic in [Arithmetic::Exact, Arithmetic::Verified] {
+            let solution = problem.clone().maximise(a + b)
+                .using(my_solver)
+                .with_arithmetic(arithmetic)
+                .with(constraint!(3*a + b <= 1))
+                .with(constraint!(a + 3*b <= 1))
+                .solve().unwrap();
+
+            let quarter = BigRational::new(1.into(), 4.into());
+            assert_eq!(solution.exact_value(a), Some(&quarter));
+            assert_eq!(solution.exact_value(b), Some(&quarter));
+            assert_eq!(solution.exact_objective, Some(BigRational::new(1.into(), 2.into())));
+            assert_eq!(solution.value(a), 0.25);
+        }
+    }
+
+    #[test]
+    fn test_bounds_and_minimisation() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().max(2));
+        let y = problem.add(variable().min(1).max(4));
+        let t = problem.add(variable());
+
+        // min |x - 3| + y with x <= 2, y >= 1
+        let solution = problem.minimise(t + y)
+            .using(my_solver)
+            .with(constraint!(x - 3 <= t))
+            .with(constraint!(-(x - 3) <= t))
+            .solve().unwrap();
 
+        assert!((solution.value(x) - 2.).abs() < 1e-6);
+        assert!((solution.value(y) - 1.).abs() < 1e-6);
+        assert!((solution.value(t) - 1.).abs() < 1e-6);
     }
 }
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::fmt;
+use good_lp::{variable::UnsolvedProblem, Constraint, IntoAffineExpression, Solution, Variable};
+use serde::Serialize;
+use crate::solution::CustomSolution;
+
+/// Violations up to this are accepted
+const FEAS_TOL: f64 = 1e-6;
+
+/// What `CustomSolver` does with an infeasible start solution
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum StartPolicy {
+    /// Drops it and records the violation as `StartOutcome::Rejected`
+    Reject,
+    /// Keeps the integer values and re-solves for the continuous ones, rejects it if that fails
+    Repair,
+    /// Fails the solve
+    Error,
+}
+
+/// What `CustomSolver` did with a start solution that does not fit the model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum StartOutcome {
+    Repaired(StartViolation),
+    Rejected(StartViolation),
+}
+
+/// First reason found why a solution does not fit the model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum StartViolation {
+    WrongLength { expected: usize, found: usize },
+    Bound { variable: usize, value: f64, min: f64, max: f64 },
+    Integrality { variable: usize, value: f64 },
+    Constraint { index: usize, violation: f64 },
+    /// A partial MIP start could not be completed to a feasible solution
+    NoCompletion,
+}
+
+impl fmt::Display for StartViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StartViolation::WrongLength { expected, found } =>
+                write!(f, "expected values for {} variables, found {}", expected, found),
+            StartViolation::Bound { variable, value, min, max } =>
+                write!(f, "variable {} = {} is outside of [{}, {}]", variable, value, min, max),
+            StartViolation::Integrality { variable, value } =>
+                write!(f, "integer variable {} = {} is fractional", variable, value),
+            StartViolation::Constraint { index, violation } =>
+                write!(f, "constraint {} is violated by {}", index, violation),
+            StartViolation::NoCompletion =>
+                write!(f, "no feasible completion found"),
+        }
+    }
+}
+
+/// Largest violations of a solution, independent of the solver that produced it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ViolationReport {
+    pub bound: f64,
+    pub constraint: f64,
+    pub integrality: f64,
+    /// Objective recomputed from the variable values
+    pub objective: f64,
+}
+
+impl ViolationReport {
+    pub fn is_feasible(&self) -> bool {
+        self.bound.max(self.constraint).max(self.integrality) <= FEAS_TOL
+    }
+}
+
+/// Variables the model declares as integer
+pub fn integer_variables(problem: &UnsolvedProblem) -> HashSet<Variable> {
+    problem.variables.iter_variables_with_def()
+        .filter(|(_, def)| def.is_integer)
+        .map(|(var, _)| var)
+        .collect()
+}
+
+/// Measures all violations of any solution, the integer variables are taken from `int_vars`
+/// as `CustomSolver` relaxes them in the problem
+pub fn verify_solution(
+    problem: &UnsolvedProblem, constraints: &[Constraint], int_vars: &HashSet<Variable>, solution: &impl Solution
+) -> ViolationReport {
+    let mut report = ViolationReport {
+        bound: 0.,
+        constraint: 0.,
+        integrality: 0.,
+        objective: problem.objective.clone().eval_with(solution),
+    };
+
+    for (var, def) in problem.variables.iter_variables_with_def() {
+        let value = solution.value(var);
+        report.bound = worst(worst(report.bound, def.min - value), value - def.max);
+        if int_vars.contains(&var) {
+            report.integrality = worst(report.integrality, (value - value.round()).abs());
+        }
+    }
+
+    for c in constraints {
+        let lhs = c.expression.clone().eval_with(solution);
+        report.constraint = worst(report.constraint, if c.is_equality { lhs.abs() } else { lhs });
+    }
+
+    report
+}
+
+/// Larger of both violations, NaN counts as infinitely violated as `f64::max` would drop it
+fn worst(current: f64, violation: f64) -> f64 {
+    if violation.is_nan() { f64::INFINITY } else { current.max(violation) }
+}
+
+/// Checks the solution against the bounds, integrality and all constraints
+pub fn check_solution(
+    problem: &UnsolvedProblem, constraints: &[Constraint], int_vars: &HashSet<Variable>, solution: &CustomSolution
+) -> Result<(), StartViolation> {
+    let expected = problem.variables.len();
+    if solution.values().len() != expected {
+        return Err(StartViolation::WrongLength { expected, found: solution.values().len() });
+    }
+
+    for (var, def) in problem.variables.iter_variables_with_def() {
+        let value = solution.value(var);
+        // Also catches NaN, which every comparison would let pass
+        if !(def.min - FEAS_TOL..=def.max + FEAS_TOL).contains(&value) {
+            return Err(StartViolation::Bound { variable: var.index(), value, min: def.min, max: def.max });
+        }
+        if int_vars.contains(&var) && (value - value.round()).abs() > FEAS_TOL {
+            return Err(StartViolation::Integrality { variable: var.index(), value });
+        }
+    }
+
+    for (index, c) in constraints.iter().enumerate() {
+        let lhs = c.expression.clone().eval_with(solution);
+        let violation = if c.is_equality { lhs.abs() } else { lhs };
+        if violation.is_nan() || violation > FEAS_TOL {
+            return Err(StartViolation::Constraint { index, violation });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use good_lp::{constraint, variable, ProblemVariables};
+    use crate::solution::CustomSolution;
+    use super::{check_solution, verify_solution, integer_variables, StartViolation};
+
+    #[test]
+    fn test_violations() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().min(0).max(3));
+        let y = problem.add(variable().min(0));
+        let problem = problem.maximise(x + y);
+        let constraints = vec![constraint!(x + y <= 4), constraint!(x - y == 1)];
+        let int_vars: HashSet<_> = [x].into_iter().collect();
+
+        let check = |values: Vec<f64>| check_solution(&problem, &constraints, &int_vars, &CustomSolution::new(values));
+
+        assert_eq!(check(vec![2., 1.]), Ok(()));
+        assert_eq!(check(vec![2.]), Err(StartViolation::WrongLength { expected: 2, found: 1 }));
+        assert_eq!(check(vec![4., 3.]), Err(StartViolation::Bound { variable: 0, value: 4., min: 0., max: 3. }));
+        assert_eq!(check(vec![1.5, 0.5]), Err(StartViolation::Integrality { variable: 0, value: 1.5 }));
+        assert_eq!(check(vec![3., 2.]), Err(StartViolation::Constraint { index: 0, violation: 1. }));
+        assert_eq!(check(vec![2., 2.]), Err(StartViolation::Constraint { index: 1, violation: 1. }));
+        assert!(matches!(check(vec![f64::NAN, 1.]), Err(StartViolation::Bound { variable: 0, .. })));
+
+        // Infinite values within the bounds make the constraint side NaN
+        let mut problem = ProblemVariables::new();
+        let (x, y) = (problem.add(variable()), problem.add(variable()));
+        let problem = problem.minimise(x);
+        let constraints = vec![constraint!(x - y <= 0)];
+        let solution = CustomSolution::new(vec![f64::INFINITY, f64::INFINITY]);
+        assert!(matches!(check_solution(&problem, &constraints, &HashSet::new(), &solution),
+            Err(StartViolation::Constraint { index: 0, .. })));
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        let mut problem = ProblemVariables::new();
+        let x = problem.add(variable().integer().min(0).max(3));
+        let y = problem.add(variable().min(0));
+        let problem = problem.maximise(x + 2 * y);
+        let constraints = vec![constraint!(x + y <= 4), constraint!(x - y == 1)];
+        let int_vars = integer_variables(&problem);
+        assert_eq!(int_vars, [x].into_iter().collect());
+
+        let report = verify_solution(&problem, &constraints, &int_vars, &CustomSolution::new(vec![2., 1.]));
+        assert!(report.is_feasible());
+        assert_eq!(report.objective, 4.);
+
+        // Every kind of violation at once, only the largest is kept
+        let report = verify_solution(&problem, &constraints, &int_vars, &CustomSolution::new(vec![3.5, -1.]));
+        assert!(!report.is_feasible());
+        assert_eq!((report.bound, report.constraint, report.integrality), (1., 3.5, 0.5));
+        assert_eq!(report.objective, 1.5);
+
+        let report = verify_solution(&problem, &constraints, &int_vars, &CustomSolution::new(vec![f64::NAN, 1.]));
+        assert!(!report.is_feasible());
+        assert_eq!((report.bound, report.constraint, report.integrality), (f64::INFINITY, f64::INFINITY, f64::INFINITY));
+    }
+}
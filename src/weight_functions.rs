@@ -4,8 +4,7 @@ pub fn equal_distribution(num_weigths: usize, value: f64) -> Vec<f64> {
     vec![value; num_weigths]
 }
 
-pub fn random_distribution(num_weigths: usize, min_value: f64, max_value: f64) -> Vec<f64> {
-    let mut rng = rand::thread_rng();
+pub fn random_distribution(num_weigths: usize, min_value: f64, max_value: f64, rng: &mut impl Rng) -> Vec<f64> {
     (0..num_weigths)
         .map(|_| rng.gen_range(min_value..max_value))
         .collect()
@@ -17,3 +16,297 @@ pub fn linear_distribution(num_weights: usize, min_value: f64, max_value: f64) -
         .map(|i| min_value + i as f64 * delta)
         .collect()
 }
+
+/// Standard normal sample by the Box-Muller transform
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = 1. - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2. * u1.ln()).sqrt() * (2. * std::f64::consts::PI * u2).cos()
+}
+
+/// Redraws of a single weight before the accepted range is considered empty
+const MAX_DRAWS: usize = 1_000_000;
+
+/// First accepted sample, panics after `MAX_DRAWS` rejected ones
+fn draw_until(mut sample: impl FnMut() -> f64, accept: impl Fn(f64) -> bool) -> f64 {
+    (0..MAX_DRAWS)
+        .map(|_| sample())
+        .find(|w| accept(*w))
+        .expect("accepted range has negligible probability")
+}
+
+/// Normal samples outside of `[min_value, max_value]` are redrawn.
+/// Panics if the range is empty or too far in the tails to be hit.
+pub fn truncated_normal_distribution(
+    num_weights: usize, mean: f64, std_dev: f64, min_value: f64, max_value: f64, rng: &mut impl Rng
+) -> Vec<f64> {
+    assert!(min_value <= max_value, "empty range [{}, {}]", min_value, max_value);
+    (0..num_weights)
+        .map(|_| draw_until(
+            || mean + std_dev * standard_normal(rng),
+            |w| (min_value..=max_value).contains(&w),
+        ))
+        .collect()
+}
+
+/// Panics for a non-positive rate
+pub fn exponential_distribution(num_weights: usize, rate: f64, rng: &mut impl Rng) -> Vec<f64> {
+    assert!(rate > 0., "rate {} is not positive", rate);
+    (0..num_weights)
+        .map(|_| -(1. - rng.gen::<f64>()).ln() / rate)
+        .collect()
+}
+
+/// Power law with weights of at least `scale`, heavier tails for smaller `shape`.
+/// Panics for a non-positive scale or shape.
+pub fn pareto_distribution(num_weights: usize, scale: f64, shape: f64, rng: &mut impl Rng) -> Vec<f64> {
+    assert!(scale > 0. && shape > 0., "scale {} and shape {} must be positive", scale, shape);
+    (0..num_weights)
+        .map(|_| scale / (1. - rng.gen::<f64>()).powf(1. / shape))
+        .collect()
+}
+
+/// Normal around randomly chosen centers, two centers give a bimodal distribution.
+/// Non-positive samples are redrawn, panics without centers or if positive samples are too rare.
+pub fn clustered_distribution(num_weights: usize, centers: &[f64], std_dev: f64, rng: &mut impl Rng) -> Vec<f64> {
+    assert!(!centers.is_empty(), "clustered distribution without centers");
+    (0..num_weights)
+        .map(|_| draw_until(
+            || centers[rng.gen_range(0..centers.len())] + std_dev * standard_normal(rng),
+            |w| w > 0.,
+        ))
+        .collect()
+}
+
+/// One weight of `giant_value` at a random position, the others uniform in `[min_value, max_value)`
+pub fn one_giant_distribution(
+    num_weights: usize, giant_value: f64, min_value: f64, max_value: f64, rng: &mut impl Rng
+) -> Vec<f64> {
+    let mut weights = random_distribution(num_weights, min_value, max_value, rng);
+    if num_weights > 0 {
+        weights[rng.gen_range(0..num_weights)] = giant_value;
+    }
+    weights
+}
+
+/// Bits of integers that are still exactly representable as `f64`
+const MAX_BITS: u32 = 52;
+
+/// Uniform integers in `[1, 2^bits)`, panics for more than `MAX_BITS` bits
+pub fn integer_distribution(num_weights: usize, bits: u32, rng: &mut impl Rng) -> Vec<f64> {
+    random_integers(num_weights, bits, rng).into_iter().map(|w| w as f64).collect()
+}
+
+fn random_integers(num_weights: usize, bits: u32, rng: &mut impl Rng) -> Vec<u64> {
+    assert!(bits <= MAX_BITS, "{} bits exceed the {} exactly representable ones", bits, MAX_BITS);
+    let max = 1u64 << bits.max(1);
+    (0..num_weights)
+        .map(|_| rng.gen_range(1..max))
+        .collect()
+}
+
+/// Integers with `kappa * num_weights` bits. Random instances are easy with many perfect partitions
+/// below `kappa = 1` and become hard without perfect partition above it.
+/// Panics if this exceeds `MAX_BITS` bits, the instance would not be at the requested ratio.
+pub fn phase_transition_distribution(num_weights: usize, kappa: f64, rng: &mut impl Rng) -> Vec<f64> {
+    let bits = (kappa * num_weights as f64).round().max(1.) as u32;
+    integer_distribution(num_weights, bits, rng)
+}
+
+/// Integers in `[1, 2^bits)` built from two halves with equal sums,
+/// panics for less than two weights or more than `MAX_BITS` bits
+pub fn perfect_partition_distribution(num_weights: usize, bits: u32, rng: &mut impl Rng) -> Vec<f64> {
+    assert!(num_weights >= 2, "a perfect partition of positive weights needs at least two of them");
+    assert!(bits <= MAX_BITS, "{} bits exceed the {} exactly representable ones", bits, MAX_BITS);
+    let max = 1u64 << bits.max(2);
+    let (num_left, num_right) = (num_weights / 2, num_weights - num_weights / 2);
+
+    let mut left: Vec<u64> = (0..num_left).map(|_| rng.gen_range(1..max)).collect();
+    // The right half has at most one more item, so its sum must exceed its count
+    if left.iter().sum::<u64>() < num_right as u64 {
+        left[0] += 1;
+    }
+    let target: u64 = left.iter().sum();
+
+    // Moves the right half towards the sum of the left half within the bounds
+    let mut right: Vec<u64> = (0..num_right).map(|_| rng.gen_range(1..max)).collect();
+    let mut sum: u64 = right.iter().sum();
+    while sum != target {
+        let i = rng.gen_range(0..num_right);
+        if sum < target {
+            let step = (target - sum).min(max - 1 - right[i]);
+            right[i] += step;
+            sum += step;
+        } else {
+            let step = (sum - target).min(right[i] - 1);
+            right[i] -= step;
+            sum -= step;
+        }
+    }
+
+    let mut weights: Vec<_> = left.into_iter().chain(right).map(|w| w as f64).collect();
+    for i in (1..weights.len()).rev() {
+        weights.swap(i, rng.gen_range(0..=i));
+    }
+    weights
+}
+
+/// Integers in `[1, 2^bits)` with an odd sum, so the difference is at least 1.
+/// Panics if `bits + ceil(log2(num_weights))` exceeds `MAX_BITS + 1`, the sum would not be exact as `f64`.
+pub fn no_perfect_partition_distribution(num_weights: usize, bits: u32, rng: &mut impl Rng) -> Vec<f64> {
+    let bits = bits.max(2);
+    let sum_bits = bits + usize::BITS - num_weights.saturating_sub(1).leading_zeros();
+    assert!(sum_bits <= MAX_BITS + 1, "sums of {} weights of {} bits are not exactly representable", num_weights, bits);
+
+    let mut weights = random_integers(num_weights, bits, rng);
+    if weights.iter().map(|w| *w as u128).sum::<u128>() % 2 == 0 {
+        if let Some(w) = weights.first_mut() {
+            *w = if *w > 1 { *w - 1 } else { *w + 1 };
+        }
+    }
+    weights.into_iter().map(|w| w as f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+    use super::{random_distribution, integer_distribution, perfect_partition_distribution, phase_transition_distribution,
+        no_perfect_partition_distribution, truncated_normal_distribution, exponential_distribution,
+        pareto_distribution, clustered_distribution, one_giant_distribution};
+    use crate::partitioning::{PartitionSolver, PartitionAlgorithm};
+
+    #[test]
+    fn test_random_seeded() {
+        let weights1 = random_distribution(10, 1., 100., &mut StdRng::seed_from_u64(3));
+        let weights2 = random_distribution(10, 1., 100., &mut StdRng::seed_from_u64(3));
+        assert_eq!(weights1, weights2);
+        assert!(weights1.iter().all(|w| (1. ..100.).contains(w)));
+    }
+
+    #[test]
+    fn test_integer() {
+        let weights = integer_distribution(100, 4, &mut StdRng::seed_from_u64(0));
+        assert!(weights.iter().all(|w| w.fract() == 0. && (1. ..16.).contains(w)));
+    }
+
+    #[test]
+    fn test_perfect_partition() {
+        for (num_weights, seed) in [(2, 0), (7, 1), (12, 2)] {
+            let weights = perfect_partition_distribution(num_weights, 10, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(weights.len(), num_weights);
+            assert!(weights.iter().all(|w| w.fract() == 0. && (1. ..1024.).contains(w)));
+
+            let result = PartitionSolver::new(PartitionAlgorithm::CompleteKarmarkarKarp).solve(&weights);
+            assert!(result.values[num_weights] < 1e-6);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_perfect_partition_single() {
+        perfect_partition_distribution(1, 10, &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_phase_transition_too_many_bits() {
+        // 1.5 * 40 = 60 bits
+        phase_transition_distribution(40, 1.5, &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_no_perfect_partition() {
+        for seed in 0..10 {
+            let weights = no_perfect_partition_distribution(9, 3, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(weights.iter().sum::<f64>() % 2., 1.);
+            assert!(weights.iter().all(|w| (1. ..8.).contains(w)));
+        }
+
+        // Sums of up to 2^53 are still exact
+        let weights = no_perfect_partition_distribution(1024, 43, &mut StdRng::seed_from_u64(0));
+        assert_eq!(weights.iter().map(|w| *w as u64).sum::<u64>() % 2, 1);
+        assert!(weights.iter().all(|w| w.fract() == 0. && (1. ..2f64.powi(43)).contains(w)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_no_perfect_partition_inexact_sum() {
+        no_perfect_partition_distribution(1025, 43, &mut StdRng::seed_from_u64(0));
+    }
+
+    fn mean(weights: &[f64]) -> f64 {
+        weights.iter().sum::<f64>() / weights.len() as f64
+    }
+
+    #[test]
+    fn test_truncated_normal() {
+        let weights = truncated_normal_distribution(1000, 50., 20., 40., 100., &mut StdRng::seed_from_u64(0));
+        assert!(weights.iter().all(|w| (40. ..=100.).contains(w)));
+        assert!(mean(&weights) > 50.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truncated_normal_empty() {
+        truncated_normal_distribution(1, 50., 20., 100., 40., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truncated_normal_tail() {
+        // 50 standard deviations away from the mean
+        truncated_normal_distribution(1, 0., 1., 50., 60., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_exponential() {
+        let weights = exponential_distribution(10_000, 0.5, &mut StdRng::seed_from_u64(0));
+        assert!(weights.iter().all(|w| *w >= 0.));
+        assert!((mean(&weights) - 2.).abs() < 0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_exponential_zero_rate() {
+        exponential_distribution(1, 0., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_pareto() {
+        let weights = pareto_distribution(10_000, 1., 3., &mut StdRng::seed_from_u64(0));
+        assert!(weights.iter().all(|w| *w >= 1.));
+        assert!((mean(&weights) - 1.5).abs() < 0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pareto_negative_shape() {
+        pareto_distribution(1, 1., -2., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pareto_zero_scale() {
+        pareto_distribution(1, 0., 3., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_clustered() {
+        let weights = clustered_distribution(1000, &[10., 100.], 1., &mut StdRng::seed_from_u64(0));
+        assert!(weights.iter().all(|w| (*w - 10.).abs() < 6. || (*w - 100.).abs() < 6.));
+        assert!(weights.iter().any(|w| *w < 50.) && weights.iter().any(|w| *w > 50.));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_clustered_without_centers() {
+        clustered_distribution(1, &[], 1., &mut StdRng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_one_giant() {
+        let weights = one_giant_distribution(20, 1000., 1., 10., &mut StdRng::seed_from_u64(0));
+        assert_eq!(weights.iter().filter(|w| **w == 1000.).count(), 1);
+        assert_eq!(weights.iter().filter(|w| (1. ..10.).contains(*w)).count(), 19);
+    }
+}